@@ -0,0 +1,296 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+use entity::flocking::Flocking;
+use entity::world::World;
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::{Delay, Frame, RgbaImage};
+
+use crate::raster::{Canvas, BACKGROUND};
+use crate::{advance, UPDATES_PER_SECOND};
+use entity::render::draw_creature;
+
+// Trades a little colour accuracy for a much quicker encode, 1 is best and slowest
+const GIF_SPEED: i32 = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Gif,
+    Apng,
+}
+
+impl Format {
+    // Picked from the extension, .png and .apng both give an APNG
+    pub fn from_path(path: &Path) -> Option<Format> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "gif" => Some(Format::Gif),
+            "png" | "apng" => Some(Format::Apng),
+            _ => None,
+        }
+    }
+}
+
+// Frames in a capture of seconds at fps, the same count record draws
+pub fn frame_count(seconds: f32, fps: u32) -> u32 {
+    (seconds.max(0.0) * fps.max(1) as f32).round() as u32
+}
+
+/*
+    Steps the world on the same fixed step as the window and draws it
+    fps times a simulated second. Frame n is taken once
+
+    steps = ( n + 1 ) * UPDATES_PER_SECOND / fps
+
+    updates have run, so frames stay evenly spaced even when fps does
+    not divide the update rate. Each frame is handed to frame as soon
+    as it is drawn rather than kept, a long capture never holds more
+    than one.
+*/
+pub fn record<E>(
+    world: &mut World,
+    flocking: Option<&Flocking>,
+    seconds: f32,
+    fps: u32,
+    width: u32,
+    height: u32,
+    mut frame: impl FnMut(RgbaImage) -> Result<(), E>,
+) -> Result<(), E> {
+    let dt = 1.0 / UPDATES_PER_SECOND as f32;
+    let frames = frame_count(seconds, fps) as u64;
+    let fps = fps.max(1) as u64;
+
+    let mut steps = 0;
+    for index in 0..frames {
+        while steps < (index + 1) * UPDATES_PER_SECOND / fps {
+            advance(world, flocking, None, dt);
+            steps += 1;
+        }
+
+        let mut canvas = Canvas::new(width, height, BACKGROUND);
+        for (_, creature) in world.iter() {
+            draw_creature(&mut canvas, creature);
+        }
+        frame(canvas.into_image())?;
+    }
+
+    Ok(())
+}
+
+enum Encoder {
+    Gif(GifEncoder<BufWriter<File>>),
+    Apng {
+        writer: png::Writer<BufWriter<File>>,
+        frames: u32, // Count the file was made for
+        written: u32,
+    },
+}
+
+/*
+    An animation file being written a frame at a time, looping forever
+    with each frame shown for 1 / fps seconds. An APNG states its frame
+    count up front so it has to be known when the file is made.
+*/
+pub struct FrameWriter {
+    encoder: Encoder,
+    fps: u32,
+}
+
+impl FrameWriter {
+    pub fn create(
+        path: &Path,
+        format: Format,
+        width: u32,
+        height: u32,
+        frames: u32,
+        fps: u32,
+    ) -> Result<FrameWriter, Box<dyn Error>> {
+        let fps = fps.max(1);
+        let file = BufWriter::new(File::create(path)?);
+
+        let encoder = match format {
+            Format::Gif => {
+                let mut encoder = GifEncoder::new_with_speed(file, GIF_SPEED);
+                encoder.set_repeat(Repeat::Infinite)?;
+                Encoder::Gif(encoder)
+            }
+            // image only writes still PNGs, so animation goes through png directly
+            Format::Apng => {
+                if frames == 0 {
+                    return Err("an APNG needs at least one frame".into());
+                }
+
+                let mut encoder = png::Encoder::new(file, width, height);
+                encoder.set_color(png::ColorType::Rgba);
+                encoder.set_depth(png::BitDepth::Eight);
+                encoder.set_animated(frames, 0)?;
+                encoder.set_frame_delay(1, fps.min(u16::MAX as u32) as u16)?;
+                Encoder::Apng {
+                    writer: encoder.write_header()?,
+                    frames,
+                    written: 0,
+                }
+            }
+        };
+
+        Ok(FrameWriter { encoder, fps })
+    }
+
+    pub fn write(&mut self, image: RgbaImage) -> Result<(), Box<dyn Error>> {
+        match &mut self.encoder {
+            Encoder::Gif(encoder) => encoder.encode_frame(Frame::from_parts(
+                image,
+                0,
+                0,
+                Delay::from_numer_denom_ms(1000, self.fps),
+            ))?,
+            Encoder::Apng {
+                writer, written, ..
+            } => {
+                writer.write_image_data(image.as_raw())?;
+                *written += 1;
+            }
+        }
+        Ok(())
+    }
+
+    // Flushes the file, an APNG given fewer frames than it was made for is an error
+    pub fn finish(self) -> Result<(), Box<dyn Error>> {
+        match self.encoder {
+            Encoder::Gif(encoder) => drop(encoder),
+            Encoder::Apng {
+                writer,
+                frames,
+                written,
+            } => {
+                if written < frames {
+                    return Err(format!("APNG made for {frames} frames was given {written}").into());
+                }
+                writer.finish()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// Records straight into the file at path, encoding each frame as it is drawn
+pub fn save(
+    path: &Path,
+    format: Format,
+    world: &mut World,
+    flocking: Option<&Flocking>,
+    seconds: f32,
+    fps: u32,
+    (width, height): (u32, u32),
+) -> Result<(), Box<dyn Error>> {
+    let frames = frame_count(seconds, fps);
+    let mut writer = FrameWriter::create(path, format, width, height, frames, fps)?;
+    record(world, flocking, seconds, fps, width, height, |image| {
+        writer.write(image)
+    })?;
+    writer.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use entity::fish::Fish;
+    use entity::vector::Vec2;
+    use image::AnimationDecoder;
+
+    fn pond() -> World {
+        let mut world = World::new();
+        world.add(Fish::preset(100.0, 100.0, 320.0, 240.0).unwrap());
+        for (_, creature) in world.iter_mut() {
+            creature.seek(Vec2::new(250.0, 180.0));
+        }
+        world
+    }
+
+    fn collect(
+        world: &mut World,
+        seconds: f32,
+        fps: u32,
+        width: u32,
+        height: u32,
+    ) -> Vec<RgbaImage> {
+        let mut frames = vec![];
+        record(world, None, seconds, fps, width, height, |image| {
+            frames.push(image);
+            Ok::<_, ()>(())
+        })
+        .unwrap();
+        frames
+    }
+
+    // Unique to this process and test so parallel runs never share a file
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!(
+            "engine_runner_capture_{}_{name}",
+            std::process::id()
+        ))
+    }
+
+    #[test]
+    fn records_evenly_spaced_frames() {
+        let frames = collect(&mut pond(), 0.5, 24, 320, 240);
+        assert_eq!(frames.len(), 12);
+        assert_eq!(frame_count(0.5, 24), 12);
+        assert_ne!(frames[0], frames[11]);
+
+        // Half a second of updates have run by the last frame, however fps divides them
+        let mut world = pond();
+        collect(&mut world, 0.5, 24, 8, 8);
+        let mut stepped = pond();
+        for _ in 0..UPDATES_PER_SECOND / 2 {
+            advance(&mut stepped, None, None, 1.0 / UPDATES_PER_SECOND as f32);
+        }
+        let position = |world: &World| world.iter().next().unwrap().1.position();
+        assert_eq!(position(&world), position(&stepped));
+    }
+
+    #[test]
+    fn picks_format_from_extension() {
+        assert_eq!(Format::from_path(Path::new("a.GIF")), Some(Format::Gif));
+        assert_eq!(Format::from_path(Path::new("a.png")), Some(Format::Apng));
+        assert_eq!(Format::from_path(Path::new("a.apng")), Some(Format::Apng));
+        assert_eq!(Format::from_path(Path::new("a.mp4")), None);
+        assert_eq!(Format::from_path(Path::new("a")), None);
+    }
+
+    #[test]
+    fn written_files_hold_every_frame() {
+        let gif = temp_path("every_frame.gif");
+        save(&gif, Format::Gif, &mut pond(), None, 0.25, 20, (64, 48)).unwrap();
+        let decoder =
+            image::codecs::gif::GifDecoder::new(std::io::BufReader::new(File::open(&gif).unwrap()))
+                .unwrap();
+        assert_eq!(decoder.into_frames().count(), 5);
+
+        let apng = temp_path("every_frame.png");
+        save(&apng, Format::Apng, &mut pond(), None, 0.25, 20, (64, 48)).unwrap();
+        let reader = png::Decoder::new(File::open(&apng).unwrap())
+            .read_info()
+            .unwrap();
+        let control = reader.info().animation_control().unwrap();
+        assert_eq!(control.num_frames, 5);
+        assert_eq!(control.num_plays, 0);
+
+        std::fs::remove_file(gif).ok();
+        std::fs::remove_file(apng).ok();
+    }
+
+    #[test]
+    fn short_apng_is_an_error() {
+        let apng = temp_path("short.png");
+        let mut writer = FrameWriter::create(&apng, Format::Apng, 8, 8, 2, 10).unwrap();
+        writer.write(RgbaImage::new(8, 8)).unwrap();
+        assert!(writer.finish().is_err());
+        std::fs::remove_file(&apng).ok();
+
+        assert!(FrameWriter::create(&apng, Format::Apng, 8, 8, 0, 10).is_err());
+        std::fs::remove_file(apng).ok();
+    }
+}
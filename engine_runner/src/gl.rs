@@ -0,0 +1,79 @@
+use entity::vector::Vec2;
+use entity::{line_between_points_bounds, point_bounding_rect, polygon_points};
+use graphics::{Context, Transformed};
+use opengl_graphics::{GlGraphics, GlyphCache};
+
+use entity::render::{Colour, Renderer};
+
+/*
+    Draws through piston's graphics onto the window. Piston lines take
+    a radius rather than a width, so widths are halved on the way in.
+*/
+pub struct GlRenderer<'a> {
+    pub c: Context,
+    pub gl: &'a mut GlGraphics,
+    pub glyphs: Option<&'a mut GlyphCache<'static>>, // Text is skipped without a font
+}
+
+impl Renderer for GlRenderer<'_> {
+    // Piston polygons must be convex so the shape is cut into triangles first
+    fn fill_polygon(&mut self, colour: Colour, points: &[Vec2]) {
+        for triangle in entity::outline::triangulate(points) {
+            graphics::polygon(
+                colour,
+                &polygon_points(&triangle),
+                self.c.transform,
+                self.gl,
+            );
+        }
+    }
+
+    fn stroke_path(&mut self, colour: Colour, width: f32, points: &[Vec2], closed: bool) {
+        let segments = if closed {
+            points.len()
+        } else {
+            points.len().saturating_sub(1)
+        };
+
+        for index in 0..segments {
+            let next = &points[(index + 1) % points.len()];
+            graphics::line(
+                colour,
+                width as f64 / 2.0,
+                line_between_points_bounds(&points[index], next),
+                self.c.transform,
+                self.gl,
+            );
+        }
+    }
+
+    fn circle(&mut self, colour: Colour, centre: Vec2, radius: f32) {
+        graphics::ellipse(
+            colour,
+            point_bounding_rect(&centre, &(radius as f64)),
+            self.c.transform,
+            self.gl,
+        );
+    }
+
+    fn arc(&mut self, colour: Colour, width: f32, centre: Vec2, radius: f32, start: f32, end: f32) {
+        graphics::circle_arc(
+            colour,
+            width as f64 / 2.0,
+            start as f64,
+            end as f64,
+            point_bounding_rect(&centre, &(radius as f64)),
+            self.c.transform,
+            self.gl,
+        );
+    }
+
+    fn text(&mut self, colour: Colour, size: u32, position: Vec2, text: &str) {
+        let Some(glyphs) = self.glyphs.as_deref_mut() else {
+            return;
+        };
+
+        let transform = self.c.transform.trans(position.x as f64, position.y as f64);
+        graphics::text(colour, size, text, glyphs, transform, self.gl).ok();
+    }
+}
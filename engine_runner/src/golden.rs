@@ -0,0 +1,260 @@
+/*
+    Golden image tests. Each scenario travels a chain a fixed number of
+    updates, draws it with the software renderer and compares the frame
+    against a reference PNG checked in under tests/golden.
+
+    Pixels match when every channel is within TOLERANCE of the reference,
+    and a frame passes with at most MAX_MISMATCHED pixels off, leaving
+    room for float differences between machines to flip an edge pixel.
+
+    On failure the frame and a diff are written to tests/golden/failures,
+    mismatches in red over a faded copy of the reference. A frame of a
+    different size fails outright, with only the frame written.
+    Run with UPDATE_GOLDEN=1 to rewrite the references after a change
+    to the look is intended.
+*/
+use std::f32::consts::PI;
+use std::path::{Path, PathBuf};
+
+use entity::arena::BoundaryMode;
+use entity::chain::{Chain, MoveCommand};
+use entity::vector::Vec2;
+use image::{Rgba, RgbaImage};
+
+use crate::raster::{Canvas, BACKGROUND};
+use entity::render::{draw_chain, draw_nodes};
+
+const WIDTH: u32 = 320;
+const HEIGHT: u32 = 240;
+const DT: f32 = 1.0 / 60.0;
+
+const TOLERANCE: u8 = 2;
+const MAX_MISMATCHED: usize = 8;
+
+fn golden_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden")
+}
+
+#[derive(Debug, Default)]
+struct Comparison {
+    mismatched: usize,
+    worst: u8, // Largest channel difference seen anywhere
+    diff: Option<RgbaImage>,
+}
+
+// None when the sizes differ, there is nothing to line the pixels up against
+fn compare(actual: &RgbaImage, expected: &RgbaImage) -> Option<Comparison> {
+    if actual.dimensions() != expected.dimensions() {
+        return None;
+    }
+
+    let mut comparison = Comparison::default();
+    let mut diff = RgbaImage::new(actual.width(), actual.height());
+
+    for ((a, e), out) in actual
+        .pixels()
+        .zip(expected.pixels())
+        .zip(diff.pixels_mut())
+    {
+        let off =
+            a.0.iter()
+                .zip(e.0)
+                .map(|(a, e)| a.abs_diff(e))
+                .max()
+                .unwrap_or(0);
+        comparison.worst = comparison.worst.max(off);
+
+        *out = if off > TOLERANCE {
+            comparison.mismatched += 1;
+            Rgba([255, 0, 0, 255])
+        } else {
+            Rgba([e.0[0] / 4, e.0[1] / 4, e.0[2] / 4, 255])
+        };
+    }
+
+    comparison.diff = Some(diff);
+    Some(comparison)
+}
+
+fn render(chain: &Chain) -> RgbaImage {
+    let mut canvas = Canvas::new(WIDTH, HEIGHT, BACKGROUND);
+    draw_chain(&mut canvas, chain);
+    draw_nodes(&mut canvas, chain);
+    canvas.into_image()
+}
+
+// References are read from dir and failures written under dir/failures
+fn assert_golden(dir: &Path, name: &str, actual: &RgbaImage) {
+    let reference = dir.join(format!("{name}.png"));
+
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::create_dir_all(dir).unwrap();
+        actual.save(&reference).unwrap();
+        return;
+    }
+
+    let expected = image::open(&reference)
+        .unwrap_or_else(|err| {
+            panic!(
+                "No reference at {}, run with UPDATE_GOLDEN=1 to make one: {err}",
+                reference.display()
+            )
+        })
+        .into_rgba8();
+
+    let comparison = compare(actual, &expected);
+    if comparison
+        .as_ref()
+        .is_some_and(|comparison| comparison.mismatched <= MAX_MISMATCHED)
+    {
+        return;
+    }
+
+    let failures = dir.join("failures");
+    std::fs::create_dir_all(&failures).unwrap();
+    actual
+        .save(failures.join(format!("{name}.actual.png")))
+        .unwrap();
+
+    let Some(comparison) = comparison else {
+        panic!(
+            "{name}: frame is {}x{} but the reference is {}x{}, see {}",
+            actual.width(),
+            actual.height(),
+            expected.width(),
+            expected.height(),
+            failures.display()
+        );
+    };
+
+    if let Some(diff) = &comparison.diff {
+        diff.save(failures.join(format!("{name}.diff.png")))
+            .unwrap();
+    }
+
+    panic!(
+        "{name}: {} pixels differ by more than {TOLERANCE} (worst {}), see {}",
+        comparison.mismatched,
+        comparison.worst,
+        failures.display()
+    );
+}
+
+fn chain(x: f32, y: f32) -> Chain {
+    Chain::builder()
+        .position(x, y)
+        .spacing(14.0)
+        .radials([14.0, 16.0, 15.0, 13.0, 11.0, 9.0, 7.0, 5.0])
+        .bounds(WIDTH as f32, HEIGHT as f32)
+        .build()
+        .unwrap()
+}
+
+fn travel(chain: &mut Chain, updates: usize) {
+    for _ in 0..updates {
+        chain.travel(DT);
+    }
+}
+
+#[test]
+fn straight_swim() {
+    let mut chain = chain(80.0, 120.0);
+    chain.steer(MoveCommand {
+        right: true,
+        ..MoveCommand::default()
+    });
+    travel(&mut chain, 40);
+
+    assert_golden(&golden_dir(), "straight_swim", &render(&chain));
+}
+
+#[test]
+fn seek_turn() {
+    let mut chain = chain(80.0, 120.0);
+    chain.head.theta = 0.0;
+    chain.seek(Vec2::new(200.0, 200.0));
+    travel(&mut chain, 45);
+
+    assert_golden(&golden_dir(), "seek_turn", &render(&chain));
+}
+
+#[test]
+fn tight_curl() {
+    let mut chain = chain(80.0, 120.0);
+    chain.head.turn_rate = 2.0 * PI;
+    chain.steer(MoveCommand {
+        up: true,
+        ..MoveCommand::default()
+    });
+    travel(&mut chain, 20);
+    chain.steer(MoveCommand {
+        left: true,
+        ..MoveCommand::default()
+    });
+    travel(&mut chain, 20);
+
+    assert_golden(&golden_dir(), "tight_curl", &render(&chain));
+}
+
+#[test]
+fn wrap_across_edge() {
+    let mut chain = chain(40.0, 120.0);
+    chain.set_boundary_mode(BoundaryMode::Wrap);
+    chain.head.theta = PI;
+    chain.steer(MoveCommand {
+        left: true,
+        ..MoveCommand::default()
+    });
+    travel(&mut chain, 30);
+
+    assert_golden(&golden_dir(), "wrap_across_edge", &render(&chain));
+}
+
+#[test]
+fn comparison_allows_tolerance() {
+    let expected = RgbaImage::from_pixel(4, 4, Rgba([100, 100, 100, 255]));
+
+    let mut actual = expected.clone();
+    actual.put_pixel(0, 0, Rgba([100 + TOLERANCE, 100, 100, 255]));
+    let comparison = compare(&actual, &expected).unwrap();
+    assert_eq!(comparison.mismatched, 0);
+    assert_eq!(comparison.worst, TOLERANCE);
+
+    actual.put_pixel(1, 1, Rgba([100, 100 - TOLERANCE - 1, 100, 255]));
+    let comparison = compare(&actual, &expected).unwrap();
+    assert_eq!(comparison.mismatched, 1);
+    assert_eq!(comparison.diff.unwrap().get_pixel(1, 1).0, [255, 0, 0, 255]);
+
+    // However few pixels a frame of another size has, it never passes
+    let smaller = RgbaImage::new(2, 2);
+    assert!(compare(&smaller, &expected).is_none());
+}
+
+#[test]
+fn size_mismatch_fails_and_keeps_the_frame() {
+    // Updating takes any frame as the new reference, so there is nothing to fail
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        return;
+    }
+
+    // Kept out of the checked in references, unique so parallel runs never share it
+    let dir = std::env::temp_dir().join(format!("engine_runner_golden_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    RgbaImage::new(4, 4)
+        .save(dir.join("size_mismatch.png"))
+        .unwrap();
+
+    let actual = RgbaImage::new(2, 2);
+    let failure = std::panic::catch_unwind(|| assert_golden(&dir, "size_mismatch", &actual));
+
+    let message = failure.unwrap_err();
+    let message = message.downcast_ref::<String>().unwrap();
+    assert!(
+        message.contains("frame is 2x2 but the reference is 4x4"),
+        "{message}"
+    );
+
+    let saved = dir.join("failures/size_mismatch.actual.png");
+    assert_eq!(image::open(saved).unwrap().into_rgba8(), actual);
+    std::fs::remove_dir_all(dir).ok();
+}
@@ -2,236 +2,228 @@ extern crate glutin_window;
 extern crate graphics;
 extern crate opengl_graphics;
 extern crate piston;
-extern crate rand;
-
-use std::f32::consts::PI;
 
 use crate::piston::EventLoop;
-use entity::chain::{Chain, Sides};
-use entity::{line_between_points_bounds, point_bounding_rect};
+use capture::{save, Format};
+use entity::arena::{BoundaryMode, DEFAULT_AVOID_MARGIN};
+use entity::chain::{Chain, MoveCommand};
+use entity::fish::Fish;
+use entity::flocking::Flocking;
+use entity::lizard::Lizard;
+use entity::octopus::Octopus;
+use entity::render::{draw_chain, draw_creature, draw_nodes, Renderer, OUTLINE};
+use entity::svg::world_svg;
+use entity::vector::Vec2;
+use entity::world::World;
+use gl::GlRenderer;
 use glutin_window::GlutinWindow as Window;
-use opengl_graphics::{GlGraphics, OpenGL};
+use opengl_graphics::{GlGraphics, GlyphCache, OpenGL, TextureSettings};
 use piston::event_loop::{EventSettings, Events};
 use piston::input::{
-    Button, ButtonArgs, ButtonEvent, ButtonState, Key, RenderArgs, RenderEvent, UpdateArgs,
-    UpdateEvent,
+    Button, ButtonArgs, ButtonEvent, ButtonState, Key, MouseCursorEvent, RenderArgs, RenderEvent,
+    UpdateArgs, UpdateEvent,
 };
 use piston::window::WindowSettings;
-use rand::Rng;
+use raster::{Canvas, BACKGROUND};
+use std::path::Path;
 
-enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
-}
+mod capture;
+mod gl;
+#[cfg(test)]
+mod golden;
+mod raster;
 
-pub struct Segment {
-    x: i32,
-    y: i32,
-}
+// Simulation runs on a fixed step, frames in between are interpolated
+const UPDATES_PER_SECOND: u64 = 60;
+
+// Frames written by the render argument when no count is given
+const RENDER_FRAMES: usize = 60;
+
+// Defaults for the capture argument
+const CAPTURE_SECONDS: f32 = 4.0;
+const CAPTURE_FPS: u32 = 30;
+
+// Simulated seconds the svg argument runs before writing the pose
+const SVG_SECONDS: f32 = 2.0;
+
+// Fish spawned when started with the pond argument, they swim as a school
+const POND_FISH: usize = 24;
 
 pub struct App {
     gl: GlGraphics, // OpenGL drawing backend.
-    // segments: Vec<Segment>,
-    chain: Chain, // direction: Direction,
-                  // applex: i32,
-                  // appley: i32,
-                  // score: u32,
-                  // gameover: bool,
+    world: World,
+    previous: World, // State before the latest update, blended from when drawing
+    move_command: MoveCommand, // Arrow / WASD keys currently held
+    flocking: Option<Flocking>, // Set when the fish swim as a school
+    schooling: bool, // Off while the keys steer, the cursor turns it back on
+    goal: Option<Vec2>, // Where the school is heading, follows the cursor
+    glyphs: Option<GlyphCache<'static>>, // Font for the status line, if one was found
 }
 
 impl App {
     fn render(&mut self, args: &RenderArgs) {
-        use graphics::*;
-
-        // self.chain.travel();
-
-        const GREEN: [f32; 4] = [0.0, 1.0, 0.0, 0.01];
-        const BLUE: [f32; 4] = [0.0, 0.0, 1.0, 0.01];
-        const RED: [f32; 4] = [1.0, 0.0, 0.0, 0.01];
-        const PURPLE: [f32; 4] = [1.0, 0.0, 1.0, 1.0];
-        const PURPLEA: [f32; 4] = [1.0, 0.0, 1.0, 0.5];
         const WHITE: [f32; 4] = [0.0, 0.0, 0.0, 0.0];
 
-        self.chain.travel();
+        // Fraction of the way from the previous update to the next one
+        let alpha = (args.ext_dt * UPDATES_PER_SECOND as f64).min(1.0) as f32;
+        let world = self.previous.interpolate(&self.world, alpha);
+
+        let mode = world
+            .iter()
+            .find_map(|(_, creature)| creature.chain())
+            .map(|chain| chain.arena().mode);
+        let glyphs = self.glyphs.as_mut();
 
         self.gl.draw(args.viewport(), |c, gl| {
-            clear(WHITE, gl);
-
-            let mut node_iter = self.chain.head.children.iter();
-
-            let mut prev_sides = Sides::from((
-                &self.chain.head.point,
-                &(64.0 as f32),
-                &self.chain.head.theta,
-            ));
-
-            let mut curr = Some(node_iter.next().expect("Should have first child"));
-
-            while curr.is_some() {
-                let node = curr.unwrap();
-                // ellipse(
-                //     GREEN,
-                //     point_bounding_rect(&node.point, &(node.radial as f64)),
-                //     c.transform,
-                //     gl,
-                // );
-
-                ellipse(
-                    RED,
-                    point_bounding_rect(&node.sides.left, &8.0),
-                    c.transform,
-                    gl,
-                );
-                ellipse(
-                    RED,
-                    point_bounding_rect(&node.sides.right, &8.0),
-                    c.transform,
-                    gl,
-                );
+            graphics::clear(WHITE, gl);
 
-                line(
-                    PURPLE,
-                    1.0,
-                    line_between_points_bounds(&prev_sides.left, &node.sides.left),
-                    c.transform,
-                    gl,
-                );
-                line(
-                    PURPLE,
-                    1.0,
-                    line_between_points_bounds(&prev_sides.right, &node.sides.right),
-                    c.transform,
-                    gl,
-                );
+            let mut r = GlRenderer { c, gl, glyphs };
+            for (_, creature) in world.iter() {
+                draw_creature(&mut r, creature);
+            }
 
-                line(
-                    PURPLE,
-                    1.0,
-                    line_between_points_bounds(&prev_sides.right, &node.sides.right),
-                    c.transform,
-                    gl,
+            if let Some(mode) = mode {
+                r.text(
+                    OUTLINE,
+                    16,
+                    Vec2::new(12.0, 24.0),
+                    &format!("Boundary: {mode:?}"),
                 );
+            }
+        });
+    }
 
-                prev_sides = node.sides.clone();
-                curr = node_iter.next();
-
-                // if curr.is_none() {
-                circle_arc(
-                    PURPLE,
-                    node.radial.clone() as f64,
-                    (node.theta + (PI / 2.0)) as f64,
-                    (node.theta - (PI / 2.0)) as f64,
-                    point_bounding_rect(&node.point, &(node.radial as f64)),
-                    c.transform,
-                    gl,
+    fn update(&mut self, args: &UpdateArgs) {
+        self.previous.clone_from(&self.world);
+        advance(
+            &mut self.world,
+            self.flocking.as_ref().filter(|_| self.schooling),
+            self.goal,
+            args.dt as f32,
+        );
+    }
+
+    fn handle_button(&mut self, args: &ButtonArgs) {
+        let pressed = args.state == ButtonState::Press;
+
+        match args.button {
+            Button::Keyboard(Key::Tab) => {
+                if pressed {
+                    for (_, creature) in self.world.iter_mut() {
+                        if let Some(chain) = creature.chain_mut() {
+                            chain.set_boundary_mode(next_boundary_mode(chain.arena().mode));
+                        }
+                    }
+                }
+                return;
+            }
+            Button::Keyboard(Key::Up) | Button::Keyboard(Key::W) => self.move_command.up = pressed,
+            Button::Keyboard(Key::Down) | Button::Keyboard(Key::S) => {
+                self.move_command.down = pressed
+            }
+            Button::Keyboard(Key::Left) | Button::Keyboard(Key::A) => {
+                self.move_command.left = pressed
+            }
+            Button::Keyboard(Key::Right) | Button::Keyboard(Key::D) => {
+                self.move_command.right = pressed
+            }
+            _ => return,
+        }
+
+        // The keys steer each fish alone, so the school breaks up
+        self.schooling = false;
+        for (_, creature) in self.world.iter_mut() {
+            creature.steer(self.move_command);
+        }
+    }
+
+    // Moving the mouse hands control back from the keys to the cursor
+    fn handle_cursor(&mut self, [x, y]: [f64; 2]) {
+        self.goal = Some(Vec2::new(x as f32, y as f32));
+        self.schooling = true;
+        for (_, creature) in self.world.iter_mut() {
+            creature.seek(Vec2::new(x as f32, y as f32));
+        }
+    }
+}
+
+// One update of the simulation, shared by the window and headless captures
+fn advance(world: &mut World, flocking: Option<&Flocking>, goal: Option<Vec2>, dt: f32) {
+    if let Some(flocking) = flocking {
+        world.flock(flocking, goal);
+    }
+    world.step(dt);
+}
+
+/*
+    Creatures picked by name, settling toward the middle until the
+    cursor or keys take over. The pond swims as a school.
+*/
+fn spawn_world(scene: Option<&str>, width: f32, height: f32) -> (World, Option<Flocking>) {
+    let mut world = World::new();
+    let mut flocking = None;
+    match scene {
+        Some("octopus") => {
+            world.add(
+                Octopus::preset(width / 2.0, height / 2.0, width, height)
+                    .expect("Octopus preset should be valid"),
+            );
+        }
+        Some("lizard") => {
+            world.add(
+                Lizard::preset(200.0, 400.0, width, height).expect("Lizard preset should be valid"),
+            );
+        }
+        // A column of fish down the left of the window
+        Some("pond") => {
+            for index in 0..POND_FISH {
+                let y = height * (index as f32 + 0.5) / POND_FISH as f32;
+                world.add(
+                    Fish::preset(200.0, y, width, height).expect("Fish preset should be valid"),
                 );
-                // }
             }
+            flocking = Some(Flocking::default());
+        }
+        _ => {
+            world.add(
+                Fish::preset(200.0, 400.0, width, height).expect("Fish preset should be valid"),
+            );
+        }
+    }
 
-            ellipse(BLUE, self.chain.head.get_bounding_rect(), c.transform, gl);
-        });
+    let centre = Vec2::new(width / 2.0, height / 2.0);
+    for (_, creature) in world.iter_mut() {
+        creature.seek(centre);
+    }
+
+    (world, flocking)
+}
+
+/*
+    Runs a chain round the window without opening one and saves each
+    update as a numbered PNG, for machines with no display or GPU.
+*/
+fn render_frames(dir: &str, frames: usize, width: u32, height: u32) {
+    std::fs::create_dir_all(dir).expect("Frame directory should be creatable");
+
+    let mut chain = Chain::builder()
+        .position(200.0, 400.0)
+        .bounds(width as f32, height as f32)
+        .radials([34.0, 40.0, 36.0, 30.0, 26.0, 22.0, 18.0, 14.0, 10.0, 8.0])
+        .build()
+        .expect("Render chain should be valid");
+    chain.seek(Vec2::new(width as f32 * 0.75, height as f32 / 3.0));
+
+    for frame in 0..frames {
+        chain.travel(1.0 / UPDATES_PER_SECOND as f32);
+
+        let mut canvas = Canvas::new(width, height, BACKGROUND);
+        draw_chain(&mut canvas, &chain);
+        draw_nodes(&mut canvas, &chain);
+
+        let path = format!("{dir}/frame_{frame:04}.png");
+        canvas.save(&path).expect("Frame should save");
     }
-    // fn update(&mut self, _args: &UpdateArgs, windowx: &u32, windowy: &u32) {
-    //     if self.gameover {
-    //         return;
-    //     }
-    //     if matches!(self.direction, Direction::Up) {
-    //         self.segments.insert(
-    //             0,
-    //             Segment {
-    //                 x: self.segments[0].x,
-    //                 y: self.segments[0].y - 10,
-    //             },
-    //         );
-    //     }
-    //     if matches!(self.direction, Direction::Down) {
-    //         self.segments.insert(
-    //             0,
-    //             Segment {
-    //                 x: self.segments[0].x,
-    //                 y: self.segments[0].y + 10,
-    //             },
-    //         );
-    //     }
-    //     if matches!(self.direction, Direction::Left) {
-    //         self.segments.insert(
-    //             0,
-    //             Segment {
-    //                 x: self.segments[0].x - 10,
-    //                 y: self.segments[0].y,
-    //             },
-    //         );
-    //     }
-    //     if matches!(self.direction, Direction::Right) {
-    //         self.segments.insert(
-    //             0,
-    //             Segment {
-    //                 x: self.segments[0].x + 10,
-    //                 y: self.segments[0].y,
-    //             },
-    //         );
-    //     }
-    //     if self.check_if_collision(&windowx, &windowy) {
-    //         self.gameover = true;
-    //         return;
-    //     }
-    //     if self.segments[0].x == self.applex && self.segments[0].y == self.appley {
-    //         self.gen_apple_coords(&windowx, &windowy);
-    //         self.score += 1;
-    //     } else {
-    //         self.segments.pop();
-    //     }
-    // }
-    // fn check_if_collision(&mut self, windowx: &u32, windowy: &u32) -> bool {
-    //     if (self.segments[0].x < 0 || self.segments[0].y < 0)
-    //         || (self.segments[0].x > *windowx as i32 || self.segments[0].y > *windowy as i32)
-    //     {
-    //         return true;
-    //     }
-    //     for i in 1..self.segments.len() {
-    //         if self.segments[0].x == self.segments[i].x && self.segments[0].y == self.segments[i].y
-    //         {
-    //             return true;
-    //         }
-    //     }
-    //     return false;
-    // }
-    // fn change_directions(&mut self, args: &ButtonArgs) {
-    //     if args.state == ButtonState::Press {
-    //         if args.button == Button::Keyboard(Key::Up)
-    //             && check_directions(&self.direction, Direction::Up)
-    //         {
-    //             self.direction = Direction::Up;
-    //         }
-    //         if args.button == Button::Keyboard(Key::Down)
-    //             && check_directions(&self.direction, Direction::Down)
-    //         {
-    //             self.direction = Direction::Down;
-    //         }
-    //         if args.button == Button::Keyboard(Key::Left)
-    //             && check_directions(&self.direction, Direction::Left)
-    //         {
-    //             self.direction = Direction::Left;
-    //         }
-    //         if args.button == Button::Keyboard(Key::Right)
-    //             && check_directions(&self.direction, Direction::Right)
-    //         {
-    //             self.direction = Direction::Right;
-    //         }
-    //     }
-    // }
-    // fn gen_apple_coords(&mut self, windowx: &u32, windowy: &u32) {
-    //     self.applex = round_to_nearest_10(rand::thread_rng().gen_range(0..*windowx as i32));
-    //     self.appley = round_to_nearest_10(rand::thread_rng().gen_range(0..*windowy as i32));
-    //     for i in &self.segments {
-    //         if i.x == self.applex && i.y == self.appley {
-    //             self.gen_apple_coords(windowx, windowy);
-    //             break;
-    //         }
-    //     }
-    // }
 }
 
 pub fn main() {
@@ -240,6 +232,71 @@ pub fn main() {
     let windowx: u32 = 1200;
     let windowy: u32 = 800;
 
+    // render [dir] [frames] draws to PNGs instead of a window
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some("render") {
+        let dir = args.get(2).map_or("frames", String::as_str);
+        let frames = args
+            .get(3)
+            .and_then(|count| count.parse().ok())
+            .unwrap_or(RENDER_FRAMES);
+        render_frames(dir, frames, windowx, windowy);
+        return;
+    }
+
+    // capture [scene] [seconds] [file] [fps] writes an animated GIF, or an APNG for .png
+    if args.get(1).map(String::as_str) == Some("capture") {
+        let scene = args.get(2).map(String::as_str);
+        let seconds = args
+            .get(3)
+            .and_then(|seconds| seconds.parse().ok())
+            .unwrap_or(CAPTURE_SECONDS);
+        let path = Path::new(args.get(4).map_or("capture.gif", String::as_str));
+        let fps = args
+            .get(5)
+            .and_then(|fps| fps.parse().ok())
+            .unwrap_or(CAPTURE_FPS);
+        let format = Format::from_path(path).expect("Capture should end in .gif, .png or .apng");
+
+        let (mut world, flocking) = spawn_world(scene, windowx as f32, windowy as f32);
+        save(
+            path,
+            format,
+            &mut world,
+            flocking.as_ref(),
+            seconds,
+            fps,
+            (windowx, windowy),
+        )
+        .expect("Capture should save");
+        return;
+    }
+
+    // svg [scene] [file] [seconds] runs the scene headless then writes its pose
+    if args.get(1).map(String::as_str) == Some("svg") {
+        let scene = args.get(2).map(String::as_str);
+        let path = args.get(3).map_or("pose.svg", String::as_str);
+        let seconds: f32 = args
+            .get(4)
+            .and_then(|seconds| seconds.parse().ok())
+            .unwrap_or(SVG_SECONDS);
+
+        let (mut world, flocking) = spawn_world(scene, windowx as f32, windowy as f32);
+        let steps = (seconds.max(0.0) * UPDATES_PER_SECOND as f32).round() as u64;
+        for _ in 0..steps {
+            advance(
+                &mut world,
+                flocking.as_ref(),
+                None,
+                1.0 / UPDATES_PER_SECOND as f32,
+            );
+        }
+
+        std::fs::write(path, world_svg(&world, windowx as f32, windowy as f32))
+            .expect("SVG should save");
+        return;
+    }
+
     // Create a Glutin window.
     let mut window: Window = WindowSettings::new("Snake", [windowx, windowy])
         .transparent(true)
@@ -251,66 +308,66 @@ pub fn main() {
 
     // Create a new game and run it.
 
+    let (world, flocking) = spawn_world(
+        args.get(1).map(String::as_str),
+        windowx as f32,
+        windowy as f32,
+    );
+
     let mut app = App {
         gl: GlGraphics::new(opengl),
-        // segments: vec![
-        //     Segment { x: 50, y: 30 },
-        //     Segment { x: 40, y: 30 },
-        //     Segment { x: 30, y: 30 },
-        // ],
-        chain: Chain::create(
-            200.0,
-            200.0,
-            32.0,
-            vec![
-                32, 32, 32, 32, 32, 32, 32, 32, 32, 32, 32, 32, 32, 32, 32, 32, 32, 32, 32, 32,
-            ],
-            1000,
-            1000,
-        ),
+        move_command: MoveCommand::default(),
+        flocking,
+        schooling: true,
+        goal: None,
+        glyphs: load_font(),
+        previous: world.clone(),
+        world,
     };
 
-    let event_settings = EventSettings::new().ups(15);
+    let event_settings = EventSettings::new().ups(UPDATES_PER_SECOND);
     let mut events = Events::new(event_settings);
-    let mut already_pressed = true;
     while let Some(e) = events.next(&mut window) {
+        if let Some(args) = e.update_args() {
+            app.update(&args);
+        }
+
         if let Some(args) = e.render_args() {
             app.render(&args);
         }
 
-        // if let Some(args) = e.update_args() {
-        //     let newwindowy = windowy + 50;
-        //     already_pressed = false;
-        //     app.update(&args, &windowx, &newwindowy);
-        // }
-        // if app.gameover {
-        //     println!("Game over! Your score is: {}", app.score);
-        //     return;
-        // }
-        // if let Some(args) = e.button_args() {
-        //     if !(already_pressed) {
-        //         already_pressed = true;
-        //         app.change_directions(&args);
-        //     }
-        // }
+        if let Some(args) = e.button_args() {
+            app.handle_button(&args);
+        }
+
+        if let Some(args) = e.mouse_cursor_args() {
+            app.handle_cursor(args);
+        }
     }
 }
 
-fn round_to_nearest_10(n: i32) -> i32 {
-    let a = (n / 10) * 10 as i32;
-    let b = a + 10;
-    if n - a > b - n {
-        return b;
-    }
-    return a;
+// Any font in an assets folder near the binary, text is left out without one
+fn load_font() -> Option<GlyphCache<'static>> {
+    let assets = find_folder::Search::ParentsThenKids(3, 3)
+        .for_folder("assets")
+        .ok()?;
+    let font = std::fs::read_dir(assets)
+        .ok()?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .find(|path| path.extension().is_some_and(|ext| ext == "ttf"))?;
+
+    GlyphCache::new(font, (), TextureSettings::new()).ok()
 }
-fn check_directions(dir1: &Direction, dir2: Direction) -> bool {
-    if (matches!(dir1, Direction::Down) && matches!(dir2, Direction::Up))
-        || (matches!(dir1, Direction::Up) && matches!(dir2, Direction::Down))
-        || (matches!(dir1, Direction::Left) && matches!(dir2, Direction::Right))
-        || (matches!(dir1, Direction::Right) && matches!(dir2, Direction::Left))
-    {
-        return false;
+
+// Tab steps through the boundary behaviours
+fn next_boundary_mode(mode: BoundaryMode) -> BoundaryMode {
+    match mode {
+        BoundaryMode::Avoid { .. } => BoundaryMode::Bounce,
+        BoundaryMode::Bounce => BoundaryMode::Wrap,
+        BoundaryMode::Wrap => BoundaryMode::Clamp,
+        BoundaryMode::Clamp => BoundaryMode::Avoid {
+            margin: DEFAULT_AVOID_MARGIN,
+        },
     }
-    return true;
 }
@@ -0,0 +1,322 @@
+use std::path::Path;
+
+use entity::vector::Vec2;
+use image::{ImageResult, Rgba, RgbaImage};
+
+use entity::render::{arc_points, Colour, Renderer};
+
+// Opaque so saved frames look the same whatever they are viewed on
+pub const BACKGROUND: Colour = [0.08, 0.1, 0.14, 1.0];
+
+/*
+    CPU stand in for GlGraphics, drawing into an RgbaImage so frames
+    can be made without a window or GPU.
+
+    A pixel is covered when its centre ( x + 0.5, y + 0.5 ) is inside
+    the shape, no anti aliasing, so the same scene always gives the
+    same pixels on every machine.
+*/
+pub struct Canvas {
+    image: RgbaImage,
+}
+
+fn to_rgba(colour: Colour) -> Colour {
+    colour.map(|channel| channel.clamp(0.0, 1.0))
+}
+
+impl Canvas {
+    pub fn new(width: u32, height: u32, background: Colour) -> Canvas {
+        let mut canvas = Canvas {
+            image: RgbaImage::new(width, height),
+        };
+        canvas.clear(background);
+        canvas
+    }
+
+    pub fn into_image(self) -> RgbaImage {
+        self.image
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> ImageResult<()> {
+        self.image.save(path)
+    }
+
+    pub fn clear(&mut self, colour: Colour) {
+        let pixel = Rgba(to_rgba(colour).map(|channel| (channel * 255.0).round() as u8));
+        for out in self.image.pixels_mut() {
+            *out = pixel;
+        }
+    }
+
+    /*
+        Source over blending, channels as fractions.
+
+        out_a = src_a + dst_a * ( 1 - src_a )
+        out_c = ( src_c * src_a + dst_c * dst_a * ( 1 - src_a ) ) / out_a
+    */
+    fn blend(&mut self, x: i64, y: i64, colour: Colour) {
+        if x < 0 || y < 0 || x >= self.image.width() as i64 || y >= self.image.height() as i64 {
+            return;
+        }
+
+        let dst = self
+            .image
+            .get_pixel(x as u32, y as u32)
+            .0
+            .map(|c| c as f32 / 255.0);
+        let src_a = colour[3];
+        let out_a = src_a + dst[3] * (1.0 - src_a);
+
+        let mut out = [0u8; 4];
+        for channel in 0..3 {
+            let value = if out_a > 0.0 {
+                (colour[channel] * src_a + dst[channel] * dst[3] * (1.0 - src_a)) / out_a
+            } else {
+                0.0
+            };
+            out[channel] = (value * 255.0).round() as u8;
+        }
+        out[3] = (out_a * 255.0).round() as u8;
+
+        self.image.put_pixel(x as u32, y as u32, Rgba(out));
+    }
+
+    /*
+        Pixel rows or columns whose centres can fall between min and max,
+        cut to the 0..size the image has so off canvas or infinite
+        shapes never walk more than the image.
+    */
+    fn span(min: f32, max: f32, size: u32) -> std::ops::RangeInclusive<i64> {
+        let first = (min - 0.5).ceil().max(0.0);
+        let last = (max - 0.5).floor().min(size as f32 - 1.0);
+        first as i64..=last as i64
+    }
+
+    fn rows(&self, min: f32, max: f32) -> std::ops::RangeInclusive<i64> {
+        Canvas::span(min, max, self.image.height())
+    }
+
+    fn columns(&self, min: f32, max: f32) -> std::ops::RangeInclusive<i64> {
+        Canvas::span(min, max, self.image.width())
+    }
+
+    /*
+        Covers every pixel whose centre is within width / 2 of the
+        segment, which also rounds off the ends.
+
+        t = clamp( ( p - a ) · ( b - a ) / |b - a|², 0, 1 )
+        distance = | p - ( a + ( b - a ) * t ) |
+    */
+    fn segment(&mut self, colour: Colour, width: f32, a: Vec2, b: Vec2) {
+        let colour = to_rgba(colour);
+        let half = width / 2.0;
+        let along = b - a;
+        let length_squared = along.length_squared();
+
+        for row in self.rows(a.y.min(b.y) - half, a.y.max(b.y) + half) {
+            for column in self.columns(a.x.min(b.x) - half, a.x.max(b.x) + half) {
+                let p = Vec2::new(column as f32 + 0.5, row as f32 + 0.5);
+                let t = if length_squared > 0.0 {
+                    ((p - a).dot(along) / length_squared).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                };
+
+                if p.distance(a + along * t) <= half {
+                    self.blend(column, row, colour);
+                }
+            }
+        }
+    }
+}
+
+impl Renderer for Canvas {
+    /*
+        Scanline fill with the even odd rule, each row crosses the
+        polygon's edges at
+
+        x = a.x + ( y - a.y ) * ( b.x - a.x ) / ( b.y - a.y )
+
+        and is filled between each pair of crossings. An edge counts
+        for y in [ a.y, b.y ) so vertices are not crossed twice.
+    */
+    fn fill_polygon(&mut self, colour: Colour, points: &[Vec2]) {
+        if points.len() < 3 {
+            return;
+        }
+        let colour = to_rgba(colour);
+
+        let min_y = points.iter().map(|p| p.y).fold(f32::INFINITY, f32::min);
+        let max_y = points.iter().map(|p| p.y).fold(f32::NEG_INFINITY, f32::max);
+        let mut crossings = vec![];
+
+        for row in self.rows(min_y, max_y) {
+            let y = row as f32 + 0.5;
+            crossings.clear();
+
+            for (index, a) in points.iter().enumerate() {
+                let b = points[(index + 1) % points.len()];
+                if (a.y <= y && y < b.y) || (b.y <= y && y < a.y) {
+                    crossings.push(a.x + (y - a.y) * (b.x - a.x) / (b.y - a.y));
+                }
+            }
+            crossings.sort_by(f32::total_cmp);
+
+            for pair in crossings.chunks_exact(2) {
+                for column in self.columns(pair[0], pair[1]) {
+                    self.blend(column, row, colour);
+                }
+            }
+        }
+    }
+
+    fn stroke_path(&mut self, colour: Colour, width: f32, points: &[Vec2], closed: bool) {
+        for pair in points.windows(2) {
+            self.segment(colour, width, pair[0], pair[1]);
+        }
+        if let (true, Some(first), Some(last)) = (closed, points.first(), points.last()) {
+            self.segment(colour, width, *last, *first);
+        }
+    }
+
+    fn circle(&mut self, colour: Colour, centre: Vec2, radius: f32) {
+        let colour = to_rgba(colour);
+
+        for row in self.rows(centre.y - radius, centre.y + radius) {
+            for column in self.columns(centre.x - radius, centre.x + radius) {
+                let p = Vec2::new(column as f32 + 0.5, row as f32 + 0.5);
+                if p.distance(centre) <= radius {
+                    self.blend(column, row, colour);
+                }
+            }
+        }
+    }
+
+    fn arc(&mut self, colour: Colour, width: f32, centre: Vec2, radius: f32, start: f32, end: f32) {
+        let points = arc_points(centre, radius, start, end);
+        self.stroke_path(colour, width, &points, false);
+    }
+
+    // No font to draw with, frames are compared pixel for pixel so none is guessed at
+    fn text(&mut self, _colour: Colour, _size: u32, _position: Vec2, _text: &str) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use entity::chain::Chain;
+    use entity::render::{draw_chain, draw_nodes};
+
+    const WHITE: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+    const CLEAR: [f32; 4] = [0.0, 0.0, 0.0, 0.0];
+
+    fn lit(canvas: &Canvas) -> usize {
+        canvas.image.pixels().filter(|p| p.0[3] > 0).count()
+    }
+
+    #[test]
+    fn fills_cover_pixel_centres() {
+        let mut canvas = Canvas::new(20, 20, CLEAR);
+        let square = [
+            Vec2::new(2.0, 2.0),
+            Vec2::new(12.0, 2.0),
+            Vec2::new(12.0, 7.0),
+            Vec2::new(2.0, 7.0),
+        ];
+        canvas.fill_polygon(WHITE, &square);
+        assert_eq!(lit(&canvas), 50);
+
+        let mut canvas = Canvas::new(20, 20, CLEAR);
+        canvas.circle(WHITE, Vec2::new(10.0, 10.0), 3.0);
+        assert_eq!(lit(&canvas), 32);
+    }
+
+    #[test]
+    fn full_arc_traces_a_ring() {
+        let mut canvas = Canvas::new(40, 40, CLEAR);
+        canvas.arc(
+            WHITE,
+            2.0,
+            Vec2::new(20.0, 20.0),
+            10.0,
+            0.0,
+            std::f32::consts::TAU,
+        );
+
+        assert_ne!(canvas.image.get_pixel(29, 19).0[3], 0);
+        assert_ne!(canvas.image.get_pixel(19, 10).0[3], 0);
+        assert_eq!(canvas.image.get_pixel(20, 20).0[3], 0);
+    }
+
+    #[test]
+    fn half_alpha_blends_over_the_background() {
+        let mut canvas = Canvas::new(4, 4, [0.0, 0.0, 0.0, 1.0]);
+        canvas.stroke_path(
+            [1.0, 1.0, 1.0, 0.5],
+            2.0,
+            &[Vec2::new(0.0, 2.0), Vec2::new(4.0, 2.0)],
+            false,
+        );
+
+        assert_eq!(canvas.image.get_pixel(1, 1).0, [128, 128, 128, 255]);
+        assert_eq!(canvas.image.get_pixel(1, 3).0, [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn off_canvas_and_infinite_shapes_finish() {
+        let mut canvas = Canvas::new(16, 16, CLEAR);
+
+        canvas.circle(WHITE, Vec2::new(-1e9, 1e9), 10.0);
+        canvas.fill_polygon(
+            WHITE,
+            &[
+                Vec2::new(1e12, 1e12),
+                Vec2::new(2e12, 1e12),
+                Vec2::new(2e12, 2e12),
+            ],
+        );
+        assert_eq!(lit(&canvas), 0);
+
+        // Infinite shapes cover at most the whole canvas
+        canvas.circle(WHITE, Vec2::new(8.0, 8.0), f32::INFINITY);
+        assert_eq!(lit(&canvas), 256);
+
+        let mut canvas = Canvas::new(16, 16, CLEAR);
+        canvas.stroke_path(
+            WHITE,
+            2.0,
+            &[
+                Vec2::new(f32::NEG_INFINITY, 8.0),
+                Vec2::new(f32::INFINITY, 8.0),
+            ],
+            false,
+        );
+        canvas.arc(WHITE, 2.0, Vec2::new(8.0, 8.0), 4.0, 0.0, f32::INFINITY);
+        canvas.fill_polygon(
+            WHITE,
+            &[
+                Vec2::new(0.0, f32::NAN),
+                Vec2::new(f32::INFINITY, 0.0),
+                Vec2::new(0.0, f32::INFINITY),
+            ],
+        );
+        assert!(lit(&canvas) <= 256);
+    }
+
+    #[test]
+    fn chain_draws_inside_its_outline() {
+        let chain = Chain::builder()
+            .position(100.0, 60.0)
+            .radials([12.0, 10.0, 8.0, 6.0])
+            .build()
+            .unwrap();
+
+        let mut canvas = Canvas::new(200, 200, CLEAR);
+        draw_chain(&mut canvas, &chain);
+        draw_nodes(&mut canvas, &chain);
+
+        let head = chain.head.point;
+        assert_ne!(canvas.image.get_pixel(head.x as u32, head.y as u32).0[3], 0);
+        assert_eq!(canvas.image.get_pixel(5, 195).0, [0, 0, 0, 0]);
+    }
+}
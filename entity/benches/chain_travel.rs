@@ -0,0 +1,23 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use entity::chain::Chain;
+
+// Cost of a single travel tick as the chain grows
+fn chain_travel(c: &mut Criterion) {
+    let mut group = c.benchmark_group("chain_travel");
+
+    for nodes in [10, 100, 10_000] {
+        let mut chain = Chain::create(600.0, 400.0, 8.0, vec![6; nodes], 1200, 800).unwrap();
+
+        group.bench_with_input(BenchmarkId::from_parameter(nodes), &nodes, |b, _| {
+            b.iter(|| {
+                chain.travel(1.0 / 60.0);
+                black_box(&chain.head.point);
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, chain_travel);
+criterion_main!(benches);
@@ -0,0 +1,92 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use entity::spatial::SpatialGrid;
+use entity::vector::Vec2;
+
+const RADIUS: f32 = 50.0;
+
+// Points scattered over a 1200 x 800 window without pulling in a rng
+fn scatter(count: usize) -> Vec<Vec2> {
+    (0..count)
+        .map(|index| {
+            let t = index as f32;
+            Vec2::new((t * 7919.0) % 1200.0, (t * 104729.0) % 800.0)
+        })
+        .collect()
+}
+
+// Every point asks for its neighbours, the way flocking does each tick
+fn neighbour_queries(c: &mut Criterion) {
+    let mut group = c.benchmark_group("neighbour_queries");
+
+    for count in [100, 1_000, 10_000] {
+        let points = scatter(count);
+
+        group.bench_with_input(BenchmarkId::new("brute_force", count), &points, |b, points| {
+            let mut found = vec![];
+            b.iter(|| {
+                for centre in points {
+                    found.clear();
+                    for (index, point) in points.iter().enumerate() {
+                        if point.distance(*centre) <= RADIUS {
+                            found.push(index);
+                        }
+                    }
+                    black_box(&found);
+                }
+            })
+        });
+
+        let grid = SpatialGrid::from_points(RADIUS, &points);
+        group.bench_with_input(BenchmarkId::new("grid", count), &points, |b, points| {
+            let mut found = vec![];
+            b.iter(|| {
+                for centre in points {
+                    grid.query_radius(*centre, RADIUS, &mut found);
+                    black_box(&found);
+                }
+            })
+        });
+    }
+
+    group.finish();
+}
+
+// Moving every point a little each tick against clearing and filling again
+fn grid_update(c: &mut Criterion) {
+    let mut group = c.benchmark_group("grid_update");
+
+    for count in [1_000, 10_000] {
+        let points = scatter(count);
+        let nudged: Vec<Vec2> = points.iter().map(|p| *p + Vec2::new(1.5, -1.0)).collect();
+
+        group.bench_with_input(BenchmarkId::new("rebuild", count), &count, |b, _| {
+            let mut grid = SpatialGrid::from_points(RADIUS, &points);
+            let mut tick = 0;
+            b.iter(|| {
+                let frame = if tick % 2 == 0 { &nudged } else { &points };
+                grid.clear();
+                for (index, point) in frame.iter().enumerate() {
+                    grid.insert(index, *point);
+                }
+                tick += 1;
+            })
+        });
+
+        group.bench_with_input(BenchmarkId::new("incremental", count), &count, |b, _| {
+            let mut grid = SpatialGrid::from_points(RADIUS, &points);
+            let mut tick = 0;
+            b.iter(|| {
+                let frame = if tick % 2 == 0 { &nudged } else { &points };
+                for (index, point) in frame.iter().enumerate() {
+                    grid.insert(index, *point);
+                }
+                tick += 1;
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, neighbour_queries, grid_update);
+criterion_main!(benches);
@@ -0,0 +1,217 @@
+use crate::vector::Vec2;
+
+pub const DEFAULT_AVOID_MARGIN: f32 = 150.0;
+
+/*
+    What happens to a head that reaches the edge of the arena.
+
+    Wrap - leaves one side and comes back in the opposite one
+    Bounce - reflects off the wall like a ball
+    Clamp - stops dead against the wall and slides along it
+    Avoid - turns away smoothly once within margin of a wall
+*/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BoundaryMode {
+    Wrap,
+    Bounce,
+    Clamp,
+    Avoid { margin: f32 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Arena {
+    pub min: Vec2,
+    pub max: Vec2,
+    pub mode: BoundaryMode,
+}
+
+// Where the head ended up after meeting the arena edge
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Contained {
+    pub point: Vec2,
+    pub theta: f32,
+    pub shift: Vec2, // Distance the head was carried by wrapping, the body must follow
+}
+
+impl Arena {
+    pub fn new(width: f32, height: f32, mode: BoundaryMode) -> Arena {
+        Arena {
+            min: Vec2::ZERO,
+            max: Vec2::new(width, height),
+            mode,
+        }
+    }
+
+    pub fn width(&self) -> f32 {
+        self.max.x - self.min.x
+    }
+
+    pub fn height(&self) -> f32 {
+        self.max.y - self.min.y
+    }
+
+    pub fn contains(&self, point: &Vec2) -> bool {
+        point.x >= self.min.x
+            && point.x <= self.max.x
+            && point.y >= self.min.y
+            && point.y <= self.max.y
+    }
+
+    pub fn clamp(&self, point: &Vec2) -> Vec2 {
+        Vec2::new(
+            point.x.clamp(self.min.x, self.max.x),
+            point.y.clamp(self.min.y, self.max.y),
+        )
+    }
+
+    /*
+        Each wall within the margin pushes away along its inward normal,
+        harder the closer the point gets.
+
+        push = sum( normal * ( 1 - dist / margin ) )
+
+        The desired direction is then bent by twice the push so a head
+        right against a wall will always turn back inside.
+    */
+    pub(crate) fn avoid_heading(&self, point: &Vec2, heading: f32) -> Option<f32> {
+        let BoundaryMode::Avoid { margin } = self.mode else {
+            return None;
+        };
+
+        if margin <= 0.0 {
+            return None;
+        }
+
+        let walls = [
+            (point.x - self.min.x, Vec2::new(1.0, 0.0)),
+            (self.max.x - point.x, Vec2::new(-1.0, 0.0)),
+            (point.y - self.min.y, Vec2::new(0.0, 1.0)),
+            (self.max.y - point.y, Vec2::new(0.0, -1.0)),
+        ];
+
+        let mut push = Vec2::ZERO;
+        for (distance, normal) in walls {
+            if distance < margin {
+                push += normal * (1.0 - distance.max(0.0) / margin);
+            }
+        }
+
+        if push == Vec2::ZERO {
+            return None;
+        }
+
+        Some((Vec2::from_angle(heading) + push * 2.0).angle())
+    }
+
+    pub(crate) fn contain(&self, point: Vec2, theta: f32) -> Contained {
+        let mut contained = Contained {
+            point,
+            theta,
+            shift: Vec2::ZERO,
+        };
+
+        match self.mode {
+            BoundaryMode::Wrap => {
+                let wrapped = Vec2::new(
+                    self.min.x + (point.x - self.min.x).rem_euclid(self.width()),
+                    self.min.y + (point.y - self.min.y).rem_euclid(self.height()),
+                );
+                contained.shift = wrapped - point;
+                contained.point = wrapped;
+            }
+            /*
+                Hitting a side wall mirrors the heading left to right
+                    theta = π - theta
+                Hitting the top or bottom mirrors it up to down
+                    theta = -theta
+                The overshoot past the wall is folded back inside.
+            */
+            BoundaryMode::Bounce => {
+                let mut direction = Vec2::from_angle(theta);
+
+                if point.x < self.min.x || point.x > self.max.x {
+                    direction.x = -direction.x;
+                }
+                if point.y < self.min.y || point.y > self.max.y {
+                    direction.y = -direction.y;
+                }
+
+                contained.point = self.clamp(&(self.clamp(&point) * 2.0 - point));
+                contained.theta = direction.angle();
+            }
+            // Avoidance only steers, so clamp in case the turn rate was not enough
+            BoundaryMode::Clamp | BoundaryMode::Avoid { .. } => {
+                contained.point = self.clamp(&point);
+            }
+        }
+
+        contained
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+
+    use super::*;
+
+    #[test]
+    fn wrap_to_opposite_side() {
+        let arena = Arena::new(100.0, 50.0, BoundaryMode::Wrap);
+
+        let result = arena.contain(Vec2::new(105.0, -5.0), 0.0);
+        assert_eq!(result.point, Vec2::new(5.0, 45.0));
+        assert_eq!(result.shift, Vec2::new(-100.0, 50.0));
+        assert_eq!(result.theta, 0.0);
+
+        let inside = arena.contain(Vec2::new(20.0, 20.0), 1.0);
+        assert_eq!(inside.shift, Vec2::ZERO);
+    }
+
+    #[test]
+    fn bounce_reflects_heading() {
+        let arena = Arena::new(100.0, 100.0, BoundaryMode::Bounce);
+
+        // Heading down-right into the right wall
+        let result = arena.contain(Vec2::new(104.0, 50.0), PI / 4.0);
+        assert_eq!(result.point, Vec2::new(96.0, 50.0));
+        assert_eq!(
+            format!("{:.3}", result.theta),
+            format!("{:.3}", 3.0 * PI / 4.0)
+        );
+
+        // Heading up into the top wall
+        let result = arena.contain(Vec2::new(50.0, -2.0), -PI / 2.0);
+        assert_eq!(result.point, Vec2::new(50.0, 2.0));
+        assert_eq!(format!("{:.3}", result.theta), format!("{:.3}", PI / 2.0));
+    }
+
+    #[test]
+    fn clamp_holds_at_wall() {
+        let arena = Arena::new(100.0, 100.0, BoundaryMode::Clamp);
+
+        let result = arena.contain(Vec2::new(-20.0, 130.0), 2.0);
+        assert_eq!(result.point, Vec2::new(0.0, 100.0));
+        assert_eq!(result.theta, 2.0);
+    }
+
+    #[test]
+    fn avoid_turns_away_from_walls() {
+        let arena = Arena::new(1000.0, 1000.0, BoundaryMode::Avoid { margin: 100.0 });
+
+        // Well inside the arena leaves the heading alone
+        assert_eq!(arena.avoid_heading(&Vec2::new(500.0, 500.0), 0.0), None);
+
+        // Heading right toward the right wall gets pushed back left
+        let heading = arena.avoid_heading(&Vec2::new(990.0, 500.0), 0.0).unwrap();
+        assert_eq!(format!("{:.3}", heading.abs()), format!("{:.3}", PI));
+
+        // Sliding along the top wall gets nudged down into the arena
+        let heading = arena.avoid_heading(&Vec2::new(500.0, 50.0), 0.0).unwrap();
+        assert!(heading > 0.0 && heading < PI / 2.0);
+
+        // Other modes never steer
+        let bounce = Arena::new(1000.0, 1000.0, BoundaryMode::Bounce);
+        assert_eq!(bounce.avoid_heading(&Vec2::new(990.0, 500.0), 0.0), None);
+    }
+}
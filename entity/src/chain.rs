@@ -1,13 +1,27 @@
 use std::{f32::consts::PI, fmt::Display};
 
-use crate::helper_functions::{pull_node_on_point, Point};
+use crate::{
+    arena::{Arena, BoundaryMode, DEFAULT_AVOID_MARGIN},
+    error::ChainError,
+    helper_functions::{lerp_angle, point_bounding_rect, pull_node_on_point, wrap_angle},
+    vector::Vec2,
+};
 
-#[derive(Debug)]
+// Largest bend allowed between two consecutive segments unless set per node
+pub const DEFAULT_MAX_BEND: f32 = PI / 8.0;
+pub const DEFAULT_SPEED: f32 = 240.0;
+pub const DEFAULT_TURN_RATE: f32 = PI;
+pub const DEFAULT_ARRIVAL_RADIUS: f32 = 100.0;
+pub const DEFAULT_HEAD_RADIAL: f32 = 32.0;
+pub const DEFAULT_EYE_SIZE: f32 = 0.2;
+pub const DEFAULT_EYE_OFFSET: f32 = 0.65;
+pub const DEFAULT_EYE_ANGLE: f32 = 2.0 * PI / 5.0;
+
+#[derive(Debug, Clone)]
 pub struct Chain {
     pub head: Head,
     node_distancing: f32,
-    max_x: i32,
-    max_y: i32,
+    arena: Arena,
 }
 
 /*
@@ -20,33 +34,59 @@ pub struct Chain {
     dx = mag * cos(x)
     dy = mag * sin(x)
 */
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Head {
-    pub point: Point,
+    pub point: Vec2,
+    pub radial: f32,
     pub theta: f32,
-    pub speed: f32, // Need to decide if this is pixels /s or /tick
+    pub speed: f32,     // Pixels per second
+    pub turn_rate: f32, // Max radians the heading can change per second
+    pub desired_heading: Option<f32>,
+    pub target: Option<Vec2>,
+    pub arrival_radius: f32, // Distance from the target the head starts slowing down
+    pub eyes: Eyes,
     pub children: Vec<Node>,
 }
 
-struct HeadSides {
-    pub left: Point,
-    pub right: Point,
-    pub left_eue: Point,
-    pub right_eye: Point,
+// Sizes are fractions of the head radial so eyes scale with the head
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Eyes {
+    pub size: f32,   // Eye radius, 0 for no eyes
+    pub offset: f32, // Distance out from the centre of the head
+    pub angle: f32,  // Radians either side of the heading
+}
+
+impl Default for Eyes {
+    fn default() -> Self {
+        Eyes {
+            size: DEFAULT_EYE_SIZE,
+            offset: DEFAULT_EYE_OFFSET,
+            angle: DEFAULT_EYE_ANGLE,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct HeadSides {
+    pub left: Vec2,
+    pub right: Vec2,
+    pub left_eye: Vec2,
+    pub right_eye: Vec2,
 }
 
 #[derive(Clone, Debug)]
 pub struct Node {
-    pub point: Point,
+    pub point: Vec2,
     pub radial: f32,
     pub theta: f32,
+    pub max_bend: f32, // Radians this node's segment may turn from the one in front
     pub sides: Sides,
 }
 
 #[derive(Debug, Clone, Default)]
 pub struct Sides {
-    pub left: Point,
-    pub right: Point,
+    pub left: Vec2,
+    pub right: Vec2,
 }
 
 impl Display for Sides {
@@ -55,30 +95,31 @@ impl Display for Sides {
     }
 }
 
-impl From<(&Point, &f32, &f32)> for Sides {
-    fn from((origin, radial, heading): (&Point, &f32, &f32)) -> Self {
+/*
+    Sides sit a quarter turn either side of the heading.
+    As y points down the screen, heading - π / 2 is on the left of travel.
+
+    left = origin + radial * direction( heading - π / 2 )
+    right = origin + radial * direction( heading + π / 2 )
+*/
+impl From<(&Vec2, &f32, &f32)> for Sides {
+    fn from((origin, radial, heading): (&Vec2, &f32, &f32)) -> Self {
         Sides {
-            left: Point {
-                x: origin.x + (heading - PI / 2.0).cos() * radial,
-                y: origin.y + (heading - PI / 2.0).sin() * radial,
-            },
-            right: Point {
-                x: origin.x + (heading + PI / 2.0).cos() * radial,
-                y: origin.y + (heading + PI / 2.0).sin() * radial,
-            },
+            left: *origin + Vec2::from_angle(heading - PI / 2.0) * *radial,
+            right: *origin + Vec2::from_angle(heading + PI / 2.0) * *radial,
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Default, Clone, Copy)]
 pub struct MoveCommand {
-    up: bool,
-    right: bool,
-    down: bool,
-    left: bool,
+    pub up: bool,
+    pub right: bool,
+    pub down: bool,
+    pub left: bool,
 }
 
-fn get_desire_heading(cmd: MoveCommand) -> Option<f32> {
+pub fn get_desire_heading(cmd: MoveCommand) -> Option<f32> {
     let xdir: Option<u8> = match (cmd.left, cmd.right) {
         (true, false) => Some(1),
         (false, true) => Some(0),
@@ -115,57 +156,284 @@ fn get_desire_heading(cmd: MoveCommand) -> Option<f32> {
     Some(desire_heading)
 }
 
-impl Chain {
-    pub fn create(
-        x: f32,
-        y: f32,
-        node_distancing: f32,
-        node_radials: Vec<i16>,
-        max_x: i32,
-        max_y: i32,
-    ) -> Chain {
-        assert!(
-            node_radials.len() <= 20,
-            "There can only be 20 or less nodes"
-        );
+/*
+    Validates everything up front so a Chain that exists is always
+    safe to travel. Any number of nodes is allowed, including none,
+    in which case the head travels on its own.
+*/
+#[derive(Debug, Clone)]
+pub struct ChainBuilder {
+    position: Vec2,
+    spacing: f32,
+    radials: Vec<f32>,
+    head_radial: Option<f32>,
+    max_bends: Option<Vec<f32>>,
+    eyes: Eyes,
+    width: f32,
+    height: f32,
+    mode: BoundaryMode,
+}
 
-        let head = Head {
-            point: Point {
-                x: x.clone(),
-                y: y.clone(),
+impl Default for ChainBuilder {
+    fn default() -> Self {
+        ChainBuilder {
+            position: Vec2::ZERO,
+            spacing: 32.0,
+            radials: vec![],
+            head_radial: None,
+            max_bends: None,
+            eyes: Eyes::default(),
+            width: 1000.0,
+            height: 1000.0,
+            mode: BoundaryMode::Avoid {
+                margin: DEFAULT_AVOID_MARGIN,
             },
+        }
+    }
+}
+
+impl ChainBuilder {
+    pub fn position(mut self, x: f32, y: f32) -> Self {
+        self.position = Vec2::new(x, y);
+        self
+    }
+
+    pub fn spacing(mut self, spacing: f32) -> Self {
+        self.spacing = spacing;
+        self
+    }
+
+    pub fn radials(mut self, radials: impl IntoIterator<Item = f32>) -> Self {
+        self.radials = radials.into_iter().collect();
+        self
+    }
+
+    // Defaults to the first node's radial so the body starts flush with the head
+    pub fn head_radial(mut self, radial: f32) -> Self {
+        self.head_radial = Some(radial);
+        self
+    }
+
+    // One per radial, otherwise every node uses DEFAULT_MAX_BEND
+    pub fn max_bends(mut self, max_bends: impl IntoIterator<Item = f32>) -> Self {
+        self.max_bends = Some(max_bends.into_iter().collect());
+        self
+    }
+
+    pub fn eyes(mut self, eyes: Eyes) -> Self {
+        self.eyes = eyes;
+        self
+    }
+
+    pub fn bounds(mut self, width: f32, height: f32) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    pub fn boundary_mode(mut self, mode: BoundaryMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn build(self) -> Result<Chain, ChainError> {
+        if !self.spacing.is_finite() || self.spacing <= 0.0 {
+            return Err(ChainError::InvalidSpacing(self.spacing));
+        }
+
+        let positive = |length: f32| length.is_finite() && length > 0.0;
+        if !(positive(self.width) && positive(self.height)) {
+            return Err(ChainError::InvalidBounds {
+                width: self.width,
+                height: self.height,
+            });
+        }
+
+        let arena = Arena::new(self.width, self.height, self.mode);
+        if !arena.contains(&self.position) {
+            return Err(ChainError::OutOfBounds {
+                x: self.position.x,
+                y: self.position.y,
+            });
+        }
+
+        if let Some((index, radius)) = self
+            .radials
+            .iter()
+            .enumerate()
+            .find(|(_, radius)| !radius.is_finite() || **radius < 0.0)
+        {
+            return Err(ChainError::InvalidRadius {
+                index,
+                radius: *radius,
+            });
+        }
+
+        let head_radial = self
+            .head_radial
+            .or(self.radials.first().copied())
+            .unwrap_or(DEFAULT_HEAD_RADIAL);
+
+        if !head_radial.is_finite() || head_radial < 0.0 {
+            return Err(ChainError::InvalidHeadRadius(head_radial));
+        }
+
+        let Eyes { size, offset, .. } = self.eyes;
+        if !size.is_finite() || size < 0.0 || !offset.is_finite() || offset < 0.0 {
+            return Err(ChainError::InvalidEyes { size, offset });
+        }
+
+        let max_bends = match self.max_bends {
+            Some(max_bends) => {
+                if max_bends.len() != self.radials.len() {
+                    return Err(ChainError::NodeCountMismatch {
+                        radials: self.radials.len(),
+                        max_bends: max_bends.len(),
+                    });
+                }
+
+                if let Some((index, max_bend)) = max_bends
+                    .iter()
+                    .enumerate()
+                    .find(|(_, max_bend)| max_bend.is_nan() || **max_bend < 0.0)
+                {
+                    return Err(ChainError::InvalidMaxBend {
+                        index,
+                        max_bend: *max_bend,
+                    });
+                }
+
+                max_bends
+            }
+            None => vec![DEFAULT_MAX_BEND; self.radials.len()],
+        };
+
+        let Vec2 { x, y } = self.position;
+
+        let head = Head {
+            point: self.position,
+            radial: head_radial,
             theta: PI / 4.0,
-            speed: 8.0,
-            children: node_radials
+            speed: DEFAULT_SPEED,
+            turn_rate: DEFAULT_TURN_RATE,
+            desired_heading: None,
+            target: None,
+            arrival_radius: DEFAULT_ARRIVAL_RADIUS,
+            eyes: self.eyes,
+            children: self
+                .radials
                 .into_iter()
+                .zip(max_bends)
                 .enumerate()
-                .map(|(index, radial)| {
-                    let radial = radial as f32;
-                    let origin = Point {
-                        x: x.clone(),
-                        y: y - (node_distancing * index as f32),
-                    };
+                .map(|(index, (radial, max_bend))| {
+                    let origin = Vec2::new(x, y - (self.spacing * index as f32));
                     let heading = PI / 2f32;
                     Node {
                         sides: Sides::from((&origin, &radial, &heading)),
                         point: origin,
                         theta: heading,
+                        max_bend,
                         radial,
                     }
                 })
                 .collect(),
         };
 
-        Chain {
+        Ok(Chain {
             head,
-            node_distancing,
-            max_x,
-            max_y,
+            node_distancing: self.spacing,
+            arena,
+        })
+    }
+}
+
+#[allow(clippy::needless_arbitrary_self_type)]
+impl Chain {
+    pub fn builder() -> ChainBuilder {
+        ChainBuilder::default()
+    }
+
+    pub fn create(
+        x: f32,
+        y: f32,
+        node_distancing: f32,
+        node_radials: Vec<i16>,
+        max_x: i32,
+        max_y: i32,
+    ) -> Result<Chain, ChainError> {
+        Chain::builder()
+            .position(x, y)
+            .spacing(node_distancing)
+            .radials(node_radials.into_iter().map(f32::from))
+            .bounds(max_x as f32, max_y as f32)
+            .build()
+    }
+
+    // dt is the seconds passed since the last travel
+    pub fn travel(self: &mut Self, dt: f32) {
+        self.head.move_chain(&self.node_distancing, &self.arena, dt);
+    }
+
+    /*
+        Blends two states of the same chain so frames drawn between
+        fixed ticks move smoothly, t = 0 gives self and t = 1 gives next.
+
+        point = lerp( prev, next, t )
+        theta = prev + wrap( next - prev ) * t
+
+        Wrapping round the arena jumps too far to blend so gives next as is.
+    */
+    pub fn interpolate(&self, next: &Chain, t: f32) -> Chain {
+        let mut blended = next.clone();
+
+        let jump = self.head.point.distance(next.head.point);
+        if jump > self.arena.width().min(self.arena.height()) / 2.0 {
+            return blended;
         }
+
+        blended.head.point = self.head.point.lerp(next.head.point, t);
+        blended.head.theta = lerp_angle(self.head.theta, next.head.theta, t);
+
+        for (node, prev) in blended.head.children.iter_mut().zip(&self.head.children) {
+            node.point = prev.point.lerp(node.point, t);
+            node.theta = lerp_angle(prev.theta, node.theta, t);
+            node.update_sides();
+        }
+
+        blended
+    }
+
+    pub fn spacing(&self) -> f32 {
+        self.node_distancing
+    }
+
+    pub fn arena(&self) -> &Arena {
+        &self.arena
+    }
+
+    pub fn set_arena(&mut self, arena: Arena) {
+        self.arena = arena;
     }
 
-    pub fn travel(self: &mut Self) {
-        self.head.move_chain(&self.node_distancing);
+    pub fn set_boundary_mode(&mut self, mode: BoundaryMode) {
+        self.arena.mode = mode;
+    }
+
+    // No keys held keeps the head on its current heading
+    pub fn steer(&mut self, cmd: MoveCommand) {
+        self.head.target = None;
+        self.head.desired_heading = get_desire_heading(cmd);
+    }
+
+    // Head chases the target until told to steer some other way
+    pub fn seek(&mut self, target: Vec2) {
+        self.head.target = Some(target);
+    }
+
+    pub fn set_max_bend(&mut self, max_bend: f32) {
+        for child in &mut self.head.children {
+            child.max_bend = max_bend;
+        }
     }
 }
 
@@ -201,79 +469,134 @@ impl Display for Node {
     }
 }
 
+#[allow(clippy::needless_arbitrary_self_type)]
 impl Head {
     pub fn add_heading(self: &mut Self, delta: f32) {
-        self.theta += delta;
+        self.theta = wrap_angle(self.theta + delta);
+    }
 
-        if self.theta > PI {
-            self.theta -= 2.0 * PI;
-        }
+    /*
+        diff = desired - theta, wrapped to -π..=π so the head turns the short way
+        max_turn = turn_rate * dt
 
-        if self.theta < -PI {
-            self.theta += 2.0 * PI;
-        }
+        theta += clamp( diff, -max_turn, max_turn )
+    */
+    pub fn steer_towards(&mut self, desired: f32, dt: f32) {
+        let diff = wrap_angle(desired - self.theta);
+        let max_turn = self.turn_rate * dt;
+
+        self.add_heading(diff.clamp(-max_turn, max_turn));
     }
 
-    fn move_chain(self: &mut Self, spacing: &f32) {
-        self.point.x += self.speed * self.theta.cos();
-        self.point.y += self.speed * self.theta.sin();
+    fn move_chain(self: &mut Self, spacing: &f32, arena: &Arena, dt: f32) {
+        let step = self.seek_step(dt);
 
-        if self.point.x > 900.0 || self.point.y > 700.0 {
-            self.add_heading(-PI / 2.0);
+        // Walls nearby take priority over wherever the head wanted to go
+        let desired = arena
+            .avoid_heading(&self.point, self.desired_heading.unwrap_or(self.theta))
+            .or(self.desired_heading);
+
+        if let Some(desired) = desired {
+            self.steer_towards(desired, dt);
         }
 
-        if self.point.x < 56.0 || self.point.y < 64.0 {
-            self.add_heading(PI / 2.0);
+        self.point += Vec2::from_angle(self.theta) * step;
+
+        let contained = arena.contain(self.point, self.theta);
+        self.point = contained.point;
+        self.theta = contained.theta;
+
+        // Wrapping carries the whole body across with the head
+        if contained.shift != Vec2::ZERO {
+            for child in &mut self.children {
+                child.point += contained.shift;
+                child.update_sides();
+            }
         }
 
-        let mut child_iter = self.children.clone().into_iter();
+        // Each node is pulled toward the one in front of it, starting at the head
+        let mut prev_point = self.point;
+        let mut prev_heading = self.theta;
 
-        let mut new_children = vec![];
+        for child in &mut self.children {
+            pull_node_on_point(&prev_point, &prev_heading, child, spacing);
 
-        let push_node = pull_node_on_point(
-            &self.point,
-            child_iter.next().expect("First Child Must Exist"),
-            spacing,
-        );
+            prev_point = child.point;
+            prev_heading = child.theta;
+        }
+    }
 
-        let mut prev_point = push_node.point.clone();
+    /*
+        A head chasing a target aims straight at it and eases off inside the
+        arrival radius so it settles on the target rather than orbiting it.
+
+        step = speed * dt * min( dist / arrival_radius, 1 )
+
+        Never travels further than the target in one step.
+    */
+    fn seek_step(&mut self, dt: f32) -> f32 {
+        let Some(target) = self.target else {
+            return self.speed * dt;
+        };
+
+        let offset = target - self.point;
+        let distance = offset.length();
+
+        if distance > 0.0 {
+            self.desired_heading = Some(offset.angle());
+        }
+
+        let slowdown = if self.arrival_radius > 0.0 {
+            (distance / self.arrival_radius).min(1.0)
+        } else {
+            1.0
+        };
 
-        new_children.push(push_node);
+        (self.speed * dt * slowdown).min(distance)
+    }
 
-        while let Some(curr) = child_iter.next() {
-            let new_node = pull_node_on_point(&prev_point, curr, spacing);
+    /*
+        Head sides sit a quarter turn off the heading like a node's,
+        eyes sit angle either side of the heading, offset from the centre.
 
-            prev_point = new_node.point.clone();
+        left_eye = point + offset * radial * direction( theta - angle )
+        right_eye = point + offset * radial * direction( theta + angle )
+    */
+    pub fn sides(&self) -> HeadSides {
+        let Sides { left, right } = Sides::from((&self.point, &self.radial, &self.theta));
+        let reach = self.eyes.offset * self.radial;
 
-            new_children.push(new_node);
+        HeadSides {
+            left,
+            right,
+            left_eye: self.point + Vec2::from_angle(self.theta - self.eyes.angle) * reach,
+            right_eye: self.point + Vec2::from_angle(self.theta + self.eyes.angle) * reach,
         }
+    }
 
-        self.children = new_children;
+    pub fn eye_radius(&self) -> f32 {
+        self.eyes.size * self.radial
     }
 
     pub fn get_bounding_rect(self: &Self) -> [f64; 4] {
-        [
-            self.point.x as f64 - 32.0,
-            self.point.y as f64 - 32.0,
-            64.0,
-            64.0,
-        ]
+        point_bounding_rect(&self.point, &(self.radial as f64))
     }
 }
 
+#[allow(clippy::needless_arbitrary_self_type)]
 impl Node {
     pub fn update_sides(self: &mut Self) {
-        self.sides.left.x = self.point.x + (self.theta - PI / 2.0).cos() * self.radial;
-        self.sides.left.y = self.point.y + (self.theta - PI / 2.0).sin() * self.radial;
-
-        self.sides.right.x = self.point.x + (self.theta + PI / 2.0).cos() * self.radial;
-        self.sides.right.y = self.point.y + (self.theta + PI / 2.0).sin() * self.radial;
+        self.sides = Sides::from((&self.point, &self.radial, &self.theta));
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::helper_functions::segment_heading;
+
+    // 30 ticks a second, so default speed moves 8 pixels a tick
+    const DT: f32 = 1.0 / 30.0;
 
     #[test]
     fn validate_desire_heading() {
@@ -374,4 +697,548 @@ mod tests {
             Some(-3.0 * PI / 4.0)
         );
     }
+
+    // Cross product of the heading with the offset to a side,
+    // negative on the left of travel and positive on the right
+    fn side_cross(origin: &Vec2, heading: f32, side: &Vec2) -> f32 {
+        Vec2::from_angle(heading).cross(*side - *origin)
+    }
+
+    #[test]
+    fn sides_sweep_every_direction() {
+        let origin = Vec2 { x: 20.0, y: -30.0 };
+
+        for degree in -180..180 {
+            let heading = (degree as f32).to_radians();
+            let sides = Sides::from((&origin, &6.0, &heading));
+
+            assert!(side_cross(&origin, heading, &sides.left) < 0.0);
+            assert!(side_cross(&origin, heading, &sides.right) > 0.0);
+
+            for side in [&sides.left, &sides.right] {
+                let distance = origin.distance(*side);
+                assert_eq!(format!("{distance:.3}"), "6.000");
+            }
+        }
+    }
+
+    #[test]
+    fn update_sides_matches_from() {
+        let mut node = Chain::create(0.0, 0.0, 10.0, vec![5], 100, 100)
+            .unwrap()
+            .head
+            .children
+            .remove(0);
+
+        for degree in 0..360 {
+            node.theta = (degree as f32).to_radians();
+            node.update_sides();
+
+            let expected = Sides::from((&node.point, &node.radial, &node.theta));
+            assert_eq!(format!("{}", node.sides), format!("{expected}"));
+        }
+    }
+
+    #[test]
+    fn chain_sides_never_swap() {
+        for degree in (0..360).step_by(5) {
+            let heading = (degree as f32).to_radians();
+
+            let mut chain = Chain::create(450.0, 350.0, 10.0, vec![6; 8], 1000, 1000).unwrap();
+            chain.head.theta = wrap_angle(heading);
+
+            for _ in 0..20 {
+                chain.travel(DT);
+
+                let mut anchor = chain.head.point;
+                for child in &chain.head.children {
+                    // Theta must point from the node toward the one in front
+                    let dot = Vec2::from_angle(child.theta).dot(anchor - child.point);
+                    assert!(dot > 0.0, "Node faces away from its anchor at {degree}°");
+
+                    assert!(side_cross(&child.point, child.theta, &child.sides.left) < 0.0);
+                    assert!(side_cross(&child.point, child.theta, &child.sides.right) > 0.0);
+
+                    anchor = child.point;
+                }
+            }
+
+            // Once straightened out the body trails directly behind the head
+            let tail = chain.head.children.last().unwrap();
+            assert!(
+                wrap_angle(tail.theta - heading).abs() < 5e-2,
+                "Tail at {degree}°"
+            );
+        }
+    }
+
+    #[test]
+    fn steer_limited_by_turn_rate() {
+        let mut chain = Chain::create(0.0, 0.0, 10.0, vec![4; 3], 100, 100).unwrap();
+        chain.head.theta = 0.0;
+        chain.head.turn_rate = 0.1;
+
+        chain.head.steer_towards(PI / 2.0, 1.0);
+        assert_eq!(chain.head.theta, 0.1);
+
+        chain.head.steer_towards(-0.05, 1.0);
+        assert_eq!(format!("{:.3}", chain.head.theta), "0.000");
+        chain.head.steer_towards(-0.05, 1.0);
+        assert_eq!(format!("{:.3}", chain.head.theta), "-0.050");
+    }
+
+    #[test]
+    fn steer_takes_short_way_round() {
+        let mut chain = Chain::create(0.0, 0.0, 10.0, vec![4; 3], 100, 100).unwrap();
+        chain.head.theta = 3.0 * PI / 4.0;
+        chain.head.turn_rate = PI / 8.0;
+
+        // Up-left is closer turning through π than back through 0
+        chain.head.steer_towards(-3.0 * PI / 4.0, 1.0);
+        assert_eq!(
+            format!("{:.3}", chain.head.theta),
+            format!("{:.3}", 7.0 * PI / 8.0)
+        );
+
+        for _ in 0..3 {
+            chain.head.steer_towards(-3.0 * PI / 4.0, 1.0);
+        }
+        assert_eq!(
+            format!("{:.3}", chain.head.theta),
+            format!("{:.3}", -3.0 * PI / 4.0)
+        );
+    }
+
+    #[test]
+    fn move_command_steers_chain() {
+        let mut chain = Chain::create(300.0, 300.0, 10.0, vec![4; 3], 1000, 1000).unwrap();
+        chain.set_boundary_mode(BoundaryMode::Wrap);
+
+        chain.steer(MoveCommand {
+            left: true,
+            ..Default::default()
+        });
+        for _ in 0..40 {
+            chain.travel(DT);
+        }
+        assert_eq!(
+            format!("{:.3}", chain.head.theta.abs()),
+            format!("{:.3}", PI)
+        );
+
+        // Releasing every key keeps the current heading
+        chain.steer(MoveCommand::default());
+        chain.travel(DT);
+        assert_eq!(chain.head.desired_heading, None);
+        assert_eq!(
+            format!("{:.3}", chain.head.theta.abs()),
+            format!("{:.3}", PI)
+        );
+    }
+
+    #[test]
+    fn seek_turns_toward_target() {
+        let mut chain = Chain::create(300.0, 300.0, 10.0, vec![4; 5], 1000, 1000).unwrap();
+        chain.head.theta = 0.0;
+        chain.head.turn_rate = PI / 16.0 / DT;
+
+        // Target straight above, so the head has to turn a quarter
+        chain.seek(Vec2::new(300.0, 0.0));
+        chain.travel(DT);
+        assert_eq!(chain.head.desired_heading, Some(-PI / 2.0));
+        assert_eq!(
+            format!("{:.3}", chain.head.theta),
+            format!("{:.3}", -PI / 16.0)
+        );
+
+        for _ in 0..10 {
+            chain.travel(DT);
+        }
+        let to_target = Vec2::new(300.0, 0.0) - chain.head.point;
+        assert!(wrap_angle(to_target.angle() - chain.head.theta).abs() < 0.1);
+    }
+
+    #[test]
+    fn seek_slows_and_stops_on_arrival() {
+        let mut chain = Chain::create(0.0, 0.0, 10.0, vec![4; 5], 1000, 1000).unwrap();
+        chain.set_boundary_mode(BoundaryMode::Wrap);
+        chain.head.theta = 0.0;
+        chain.head.speed = 10.0 / DT;
+        chain.head.arrival_radius = 50.0;
+
+        let target = Vec2::new(200.0, 0.0);
+        chain.seek(target);
+
+        // Full speed outside the arrival radius
+        chain.travel(DT);
+        assert_eq!(format!("{:.3}", chain.head.point.x), "10.000");
+        assert_eq!(format!("{:.3}", chain.head.point.y), "0.000");
+
+        let mut prev_step = 10.0;
+        for _ in 0..200 {
+            let before = chain.head.point;
+            chain.travel(DT);
+            let step = before.distance(chain.head.point);
+
+            assert!(
+                step <= prev_step + 1e-4,
+                "Sped up from {prev_step} to {step}"
+            );
+            prev_step = step;
+        }
+
+        assert!(chain.head.point.distance(target) < 0.5);
+    }
+
+    #[test]
+    fn steer_cancels_seek() {
+        let mut chain = Chain::create(0.0, 0.0, 10.0, vec![4; 5], 1000, 1000).unwrap();
+
+        chain.seek(Vec2::new(100.0, 100.0));
+        chain.steer(MoveCommand {
+            right: true,
+            ..Default::default()
+        });
+
+        assert_eq!(chain.head.target, None);
+        assert_eq!(chain.head.desired_heading, Some(0.0));
+    }
+
+    #[test]
+    fn wrap_carries_body_across() {
+        let mut chain = Chain::create(95.0, 50.0, 10.0, vec![4; 4], 100, 100).unwrap();
+        chain.set_boundary_mode(BoundaryMode::Wrap);
+        chain.head.theta = 0.0;
+
+        let before: Vec<Vec2> = chain.head.children.iter().map(|c| c.point).collect();
+        chain.travel(DT);
+
+        // Head crossed the right edge so everything moved back by the width
+        assert_eq!(chain.head.point, Vec2::new(3.0, 50.0));
+        for (child, before) in chain.head.children.iter().zip(before) {
+            assert!(child.point.x < before.x);
+            assert!(child.point.distance(chain.head.point) < 100.0);
+        }
+    }
+
+    #[test]
+    fn default_mode_avoids_walls() {
+        let mut chain = Chain::create(500.0, 500.0, 10.0, vec![4; 5], 1000, 1000).unwrap();
+        assert_eq!(
+            chain.arena().mode,
+            BoundaryMode::Avoid {
+                margin: DEFAULT_AVOID_MARGIN
+            }
+        );
+
+        // Heading for the right wall at a slant, the head turns back before reaching it
+        chain.head.theta = 0.3;
+        let mut furthest: f32 = 0.0;
+        for _ in 0..200 {
+            chain.travel(DT);
+            furthest = furthest.max(chain.head.point.x);
+        }
+        assert!(furthest < 1000.0);
+        assert!(chain.head.point.x < furthest - 100.0);
+    }
+
+    #[test]
+    fn bounded_modes_keep_head_inside() {
+        let modes = [
+            BoundaryMode::Bounce,
+            BoundaryMode::Clamp,
+            BoundaryMode::Avoid { margin: 120.0 },
+        ];
+
+        for mode in modes {
+            let mut chain = Chain::create(400.0, 300.0, 10.0, vec![4; 8], 800, 600).unwrap();
+            chain.set_boundary_mode(mode);
+            chain.head.desired_heading = Some(-PI / 3.0);
+
+            for _ in 0..500 {
+                chain.travel(DT);
+                assert!(chain.arena().contains(&chain.head.point), "{mode:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn long_chain_keeps_spacing() {
+        let mut chain = Chain::create(500.0, 500.0, 4.0, vec![2; 2_000], 1000, 1000).unwrap();
+
+        for _ in 0..50 {
+            chain.travel(DT);
+        }
+
+        let mut prev_point = chain.head.point;
+        for child in &chain.head.children {
+            assert!(prev_point.distance(child.point) <= 4.0 + 1e-3);
+            prev_point = child.point;
+        }
+    }
+
+    #[test]
+    fn travel_scales_with_dt() {
+        let mut coarse = Chain::create(300.0, 500.0, 10.0, vec![4; 5], 1000, 1000).unwrap();
+        let mut fine = coarse.clone();
+        coarse.head.theta = 0.0;
+        fine.head.theta = 0.0;
+
+        coarse.travel(0.5);
+        for _ in 0..5 {
+            fine.travel(0.1);
+        }
+
+        // Half a second at 240 pixels a second however it is split up
+        let expected = Vec2::new(300.0 + DEFAULT_SPEED / 2.0, 500.0);
+        assert!(coarse.head.point.distance(expected) < 1e-3);
+        assert!(fine.head.point.distance(expected) < 1e-3);
+    }
+
+    #[test]
+    fn interpolate_between_ticks() {
+        let prev = Chain::create(300.0, 500.0, 10.0, vec![4; 5], 1000, 1000).unwrap();
+        let mut next = prev.clone();
+        next.travel(DT);
+
+        let start = prev.interpolate(&next, 0.0);
+        let end = prev.interpolate(&next, 1.0);
+        let halfway = prev.interpolate(&next, 0.5);
+
+        assert_eq!(start.head.point, prev.head.point);
+        assert_eq!(end.head.point, next.head.point);
+        assert_eq!(
+            halfway.head.point,
+            prev.head.point.lerp(next.head.point, 0.5)
+        );
+
+        for ((node, before), after) in halfway
+            .head
+            .children
+            .iter()
+            .zip(&prev.head.children)
+            .zip(&next.head.children)
+        {
+            assert_eq!(node.point, before.point.lerp(after.point, 0.5));
+            let expected = Sides::from((&node.point, &node.radial, &node.theta));
+            assert_eq!(format!("{}", node.sides), format!("{expected}"));
+        }
+    }
+
+    #[test]
+    fn interpolate_skips_wrap() {
+        let mut prev = Chain::create(95.0, 50.0, 10.0, vec![4; 4], 100, 100).unwrap();
+        prev.set_boundary_mode(BoundaryMode::Wrap);
+        prev.head.theta = 0.0;
+
+        let mut next = prev.clone();
+        next.travel(DT);
+
+        // Head wrapped from the right edge to the left, no smear across the arena
+        let halfway = prev.interpolate(&next, 0.5);
+        assert_eq!(halfway.head.point, next.head.point);
+    }
+
+    #[test]
+    fn builder_rejects_bad_input() {
+        let base = || Chain::builder().position(50.0, 50.0).bounds(100.0, 100.0);
+
+        assert_eq!(
+            base().spacing(0.0).build().unwrap_err(),
+            ChainError::InvalidSpacing(0.0)
+        );
+        assert_eq!(
+            base().spacing(-2.0).build().unwrap_err(),
+            ChainError::InvalidSpacing(-2.0)
+        );
+        assert_eq!(
+            base().radials([4.0, -1.0]).build().unwrap_err(),
+            ChainError::InvalidRadius {
+                index: 1,
+                radius: -1.0
+            }
+        );
+        assert_eq!(
+            base().radials([4.0, f32::INFINITY]).build().unwrap_err(),
+            ChainError::InvalidRadius {
+                index: 1,
+                radius: f32::INFINITY
+            }
+        );
+        assert_eq!(
+            base().bounds(0.0, 100.0).build().unwrap_err(),
+            ChainError::InvalidBounds {
+                width: 0.0,
+                height: 100.0
+            }
+        );
+        assert_eq!(
+            base().bounds(100.0, f32::INFINITY).build().unwrap_err(),
+            ChainError::InvalidBounds {
+                width: 100.0,
+                height: f32::INFINITY
+            }
+        );
+        assert_eq!(
+            base().position(150.0, 50.0).build().unwrap_err(),
+            ChainError::OutOfBounds { x: 150.0, y: 50.0 }
+        );
+        assert_eq!(
+            base()
+                .radials([4.0, 4.0])
+                .max_bends([0.2])
+                .build()
+                .unwrap_err(),
+            ChainError::NodeCountMismatch {
+                radials: 2,
+                max_bends: 1
+            }
+        );
+        assert_eq!(
+            base().radials([4.0]).max_bends([-0.2]).build().unwrap_err(),
+            ChainError::InvalidMaxBend {
+                index: 0,
+                max_bend: -0.2
+            }
+        );
+
+        assert_eq!(
+            base().head_radial(-4.0).build().unwrap_err(),
+            ChainError::InvalidHeadRadius(-4.0)
+        );
+        assert_eq!(
+            base().head_radial(f32::INFINITY).build().unwrap_err(),
+            ChainError::InvalidHeadRadius(f32::INFINITY)
+        );
+        assert_eq!(
+            base()
+                .eyes(Eyes {
+                    size: -0.1,
+                    ..Eyes::default()
+                })
+                .build()
+                .unwrap_err(),
+            ChainError::InvalidEyes {
+                size: -0.1,
+                offset: DEFAULT_EYE_OFFSET
+            }
+        );
+        assert_eq!(
+            base()
+                .eyes(Eyes {
+                    offset: f32::INFINITY,
+                    ..Eyes::default()
+                })
+                .build()
+                .unwrap_err(),
+            ChainError::InvalidEyes {
+                size: DEFAULT_EYE_SIZE,
+                offset: f32::INFINITY
+            }
+        );
+
+        // create goes through the same checks
+        assert!(Chain::create(10.0, 10.0, 5.0, vec![-3], 100, 100).is_err());
+    }
+
+    #[test]
+    fn builder_sets_nodes() {
+        let chain = Chain::builder()
+            .position(20.0, 40.0)
+            .spacing(6.0)
+            .radials([5.0, 4.0, 3.0])
+            .max_bends([0.1, 0.2, 0.3])
+            .bounds(200.0, 100.0)
+            .boundary_mode(BoundaryMode::Wrap)
+            .build()
+            .unwrap();
+
+        assert_eq!(chain.head.point, Vec2::new(20.0, 40.0));
+        assert_eq!(chain.head.radial, 5.0);
+        assert_eq!(chain.arena().mode, BoundaryMode::Wrap);
+        assert_eq!(chain.arena().max, Vec2::new(200.0, 100.0));
+
+        let radials: Vec<f32> = chain.head.children.iter().map(|c| c.radial).collect();
+        let bends: Vec<f32> = chain.head.children.iter().map(|c| c.max_bend).collect();
+        assert_eq!(radials, vec![5.0, 4.0, 3.0]);
+        assert_eq!(bends, vec![0.1, 0.2, 0.3]);
+        assert_eq!(chain.head.children[2].point, Vec2::new(20.0, 28.0));
+    }
+
+    #[test]
+    fn eyes_follow_heading() {
+        let mut chain = Chain::builder()
+            .position(100.0, 100.0)
+            .head_radial(10.0)
+            .eyes(Eyes {
+                size: 0.3,
+                offset: 0.5,
+                angle: PI / 2.0,
+            })
+            .build()
+            .unwrap();
+        chain.head.theta = 0.0;
+
+        // Heading right with eyes a quarter turn out, so straight up and down
+        let sides = chain.head.sides();
+        assert_eq!(
+            format!("{}", sides.left_eye),
+            format!("{}", Vec2::new(100.0, 95.0))
+        );
+        assert_eq!(
+            format!("{}", sides.right_eye),
+            format!("{}", Vec2::new(100.0, 105.0))
+        );
+        assert_eq!(chain.head.eye_radius(), 3.0);
+
+        // Eyes turn with the head and stay between its sides
+        chain.head.theta = PI / 2.0;
+        let sides = chain.head.sides();
+        assert!(sides.left_eye.x > chain.head.point.x);
+        assert!(sides.right_eye.x < chain.head.point.x);
+        assert!(sides.left.distance(sides.left_eye) < sides.right.distance(sides.left_eye));
+    }
+
+    #[test]
+    fn chain_without_children_travels() {
+        let mut chain = Chain::builder().position(50.0, 50.0).build().unwrap();
+        assert!(chain.head.children.is_empty());
+
+        // No nodes is a valid chain however it is made
+        let empty = Chain::builder().position(50.0, 50.0).radials([]).build();
+        assert!(empty.unwrap().head.children.is_empty());
+        let created = Chain::create(50.0, 50.0, 10.0, vec![], 100, 100);
+        assert!(created.unwrap().head.children.is_empty());
+
+        chain.seek(Vec2::new(200.0, 50.0));
+        for _ in 0..10 {
+            chain.travel(DT);
+        }
+        assert!(chain.head.point.x > 50.0);
+    }
+
+    #[test]
+    fn chain_respects_max_bend() {
+        let mut chain = Chain::create(400.0, 400.0, 10.0, vec![8; 10], 1000, 1000).unwrap();
+        chain.set_max_bend(PI / 6.0);
+
+        // Head starts heading up-right, then doubles back on the body
+        for tick in 0..40 {
+            if tick == 5 {
+                chain.head.add_heading(PI);
+            }
+            chain.travel(DT);
+
+            let mut prev_point = chain.head.point;
+            let mut prev_heading = chain.head.theta;
+            for child in &chain.head.children {
+                let heading = segment_heading(&prev_point, &child.point);
+                let bend = wrap_angle(heading - prev_heading).abs();
+                assert!(bend <= PI / 6.0 + 1e-3, "Joint bent {bend} on tick {tick}");
+
+                prev_point = child.point;
+                prev_heading = heading;
+            }
+        }
+    }
 }
@@ -0,0 +1,129 @@
+use crate::{
+    chain::{Chain, MoveCommand},
+    fish::Fish,
+    lizard::Lizard,
+    octopus::Octopus,
+    skeleton::Skeleton,
+    vector::Vec2,
+};
+
+// Anything that can live in a World
+#[derive(Debug, Clone)]
+pub enum Creature {
+    Chain(Chain),
+    Fish(Fish),
+    Lizard(Lizard),
+    Octopus(Octopus),
+    Skeleton(Skeleton),
+}
+
+impl Creature {
+    // Chain the creature swims or walks with, an octopus stays put so has none
+    pub fn chain(&self) -> Option<&Chain> {
+        match self {
+            Creature::Chain(chain) => Some(chain),
+            Creature::Fish(fish) => Some(&fish.chain),
+            Creature::Lizard(lizard) => Some(&lizard.chain),
+            Creature::Octopus(_) => None,
+            Creature::Skeleton(skeleton) => Some(&skeleton.spine),
+        }
+    }
+
+    pub fn chain_mut(&mut self) -> Option<&mut Chain> {
+        match self {
+            Creature::Chain(chain) => Some(chain),
+            Creature::Fish(fish) => Some(&mut fish.chain),
+            Creature::Lizard(lizard) => Some(&mut lizard.chain),
+            Creature::Octopus(_) => None,
+            Creature::Skeleton(skeleton) => Some(&mut skeleton.spine),
+        }
+    }
+
+    // Where the creature is, the head or the middle of the body
+    pub fn position(&self) -> Vec2 {
+        match self {
+            Creature::Octopus(octopus) => octopus.centre,
+            _ => self.chain().map_or(Vec2::ZERO, |chain| chain.head.point),
+        }
+    }
+
+    // Moving creatures head for the target, an octopus reaches for it
+    pub fn seek(&mut self, target: Vec2) {
+        match self {
+            Creature::Octopus(octopus) => octopus.reach_for(Some(target)),
+            _ => {
+                if let Some(chain) = self.chain_mut() {
+                    chain.seek(target);
+                }
+            }
+        }
+    }
+
+    pub fn steer(&mut self, cmd: MoveCommand) {
+        if let Some(chain) = self.chain_mut() {
+            chain.steer(cmd);
+        }
+    }
+
+    pub fn travel(&mut self, dt: f32) {
+        match self {
+            Creature::Chain(chain) => chain.travel(dt),
+            Creature::Fish(fish) => fish.travel(dt),
+            Creature::Lizard(lizard) => lizard.travel(dt),
+            Creature::Octopus(octopus) => octopus.travel(dt),
+            Creature::Skeleton(skeleton) => skeleton.travel(dt),
+        }
+    }
+
+    // Only blends states of the same kind, anything else gives next as is
+    pub fn interpolate(&self, next: &Creature, t: f32) -> Creature {
+        match (self, next) {
+            (Creature::Chain(prev), Creature::Chain(next)) => {
+                Creature::Chain(prev.interpolate(next, t))
+            }
+            (Creature::Fish(prev), Creature::Fish(next)) => {
+                Creature::Fish(prev.interpolate(next, t))
+            }
+            (Creature::Lizard(prev), Creature::Lizard(next)) => {
+                Creature::Lizard(prev.interpolate(next, t))
+            }
+            (Creature::Octopus(prev), Creature::Octopus(next)) => {
+                Creature::Octopus(prev.interpolate(next, t))
+            }
+            (Creature::Skeleton(prev), Creature::Skeleton(next)) => {
+                Creature::Skeleton(prev.interpolate(next, t))
+            }
+            _ => next.clone(),
+        }
+    }
+}
+
+impl From<Chain> for Creature {
+    fn from(chain: Chain) -> Self {
+        Creature::Chain(chain)
+    }
+}
+
+impl From<Fish> for Creature {
+    fn from(fish: Fish) -> Self {
+        Creature::Fish(fish)
+    }
+}
+
+impl From<Lizard> for Creature {
+    fn from(lizard: Lizard) -> Self {
+        Creature::Lizard(lizard)
+    }
+}
+
+impl From<Octopus> for Creature {
+    fn from(octopus: Octopus) -> Self {
+        Creature::Octopus(octopus)
+    }
+}
+
+impl From<Skeleton> for Creature {
+    fn from(skeleton: Skeleton) -> Self {
+        Creature::Skeleton(skeleton)
+    }
+}
@@ -0,0 +1,65 @@
+use std::{error::Error, fmt::Display};
+
+// Reasons a ChainBuilder refuses to build
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChainError {
+    InvalidSpacing(f32),
+    InvalidRadius { index: usize, radius: f32 },
+    InvalidHeadRadius(f32),
+    InvalidMaxBend { index: usize, max_bend: f32 },
+    NodeCountMismatch { radials: usize, max_bends: usize },
+    InvalidEyes { size: f32, offset: f32 },
+    InvalidBounds { width: f32, height: f32 },
+    OutOfBounds { x: f32, y: f32 },
+}
+
+impl Display for ChainError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChainError::InvalidSpacing(spacing) => {
+                write!(f, "Node spacing must be above 0, got {spacing}")
+            }
+            ChainError::InvalidRadius { index, radius } => {
+                write!(
+                    f,
+                    "Node {index} has radius {radius}, radii must be finite and not negative"
+                )
+            }
+            ChainError::InvalidHeadRadius(radius) => {
+                write!(
+                    f,
+                    "Head has radius {radius}, radii must be finite and not negative"
+                )
+            }
+            ChainError::InvalidMaxBend { index, max_bend } => {
+                write!(
+                    f,
+                    "Node {index} has max bend {max_bend}, bends cannot be negative"
+                )
+            }
+            ChainError::NodeCountMismatch { radials, max_bends } => {
+                write!(
+                    f,
+                    "{radials} node radii were given but {max_bends} max bends"
+                )
+            }
+            ChainError::InvalidEyes { size, offset } => {
+                write!(
+                    f,
+                    "Eyes have size {size} and offset {offset}, neither can be negative"
+                )
+            }
+            ChainError::InvalidBounds { width, height } => {
+                write!(
+                    f,
+                    "Bounds must be finite and above 0, got {width} x {height}"
+                )
+            }
+            ChainError::OutOfBounds { x, y } => {
+                write!(f, "Head at ({x}, {y}) starts outside the bounds")
+            }
+        }
+    }
+}
+
+impl Error for ChainError {}
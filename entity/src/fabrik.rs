@@ -0,0 +1,383 @@
+use crate::{
+    chain::{Chain, Node},
+    helper_functions::{segment_heading, wrap_angle},
+    vector::Vec2,
+};
+
+pub const DEFAULT_ITERATIONS: usize = 10;
+pub const DEFAULT_TOLERANCE: f32 = 0.01;
+
+/*
+    Joints from a pinned root out to the tip, with a fixed length
+    between each pair. Unlike follow the leader the root never moves
+    while the tip reaches for a target, as an arm or tentacle would.
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fabrik {
+    joints: Vec<Vec2>,
+    lengths: Vec<f32>,
+    pub iterations: usize,           // Most passes a single solve may take
+    pub tolerance: f32,              // Tip this close to the target counts as reached
+    pub max_bends: Vec<Option<f32>>, // Per joint, radians it may turn from the segment before it
+    pub root_heading: Option<f32>,   // Direction the first segment bends from, free if None
+}
+
+// How a solve went
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Solve {
+    pub iterations: usize,
+    pub error: f32, // Distance left between the tip and the target
+    pub reached: bool,
+}
+
+impl Fabrik {
+    /*
+        Segment lengths are taken from the gaps between the joints given.
+        Fewer than two joints leaves nothing to solve, so solve returns
+        straight away without moving anything.
+    */
+    pub fn new(joints: Vec<Vec2>) -> Fabrik {
+        let lengths: Vec<f32> = joints
+            .windows(2)
+            .map(|pair| pair[0].distance(pair[1]))
+            .collect();
+
+        Fabrik {
+            joints,
+            max_bends: vec![None; lengths.len()],
+            lengths,
+            iterations: DEFAULT_ITERATIONS,
+            tolerance: DEFAULT_TOLERANCE,
+            root_heading: None,
+        }
+    }
+
+    // Lays the joints out in a straight line from the root along heading
+    pub fn straight(root: Vec2, heading: f32, lengths: &[f32]) -> Fabrik {
+        let mut joints = vec![root];
+        for length in lengths {
+            let last = joints[joints.len() - 1];
+            joints.push(last + Vec2::from_angle(heading) * *length);
+        }
+        Fabrik::new(joints)
+    }
+
+    pub fn joints(&self) -> &[Vec2] {
+        &self.joints
+    }
+
+    // Joints can be moved but not added or removed, lengths stay one per gap
+    pub fn joints_mut(&mut self) -> &mut [Vec2] {
+        &mut self.joints
+    }
+
+    pub fn lengths(&self) -> &[f32] {
+        &self.lengths
+    }
+
+    // Furthest the tip can get from the root
+    pub fn reach(&self) -> f32 {
+        self.lengths.iter().sum()
+    }
+
+    // None for a solver made without joints
+    pub fn root(&self) -> Option<Vec2> {
+        self.joints.first().copied()
+    }
+
+    pub fn tip(&self) -> Option<Vec2> {
+        self.joints.last().copied()
+    }
+
+    // Carries the whole pose with the root, for roots fixed to a moving body
+    pub fn move_root(&mut self, root: Vec2) {
+        let Some(first) = self.joints.first() else {
+            return;
+        };
+
+        let shift = root - *first;
+        for joint in &mut self.joints {
+            *joint += shift;
+        }
+    }
+
+    /*
+        Forward And Backward Reaching Inverse Kinematics.
+
+        forward: put the tip on the target, then walk back to the root
+            joint[i] = joint[i + 1] + direction( joint[i] - joint[i + 1] ) * length[i]
+
+        backward: put the root back where it was, then walk out to the tip
+            joint[i + 1] = joint[i] + direction( joint[i + 1] - joint[i] ) * length[i]
+
+        Each pass ends with the root pinned and every length kept,
+        repeating pulls the tip closer until it is within tolerance
+        or the iterations run out. Out of reach targets settle with
+        the joints stretched straight toward them.
+
+        Bends are limited on the backward pass only so the root side
+        of the chain always ends up within its limits. max_bends[i]
+        limits joint i, the first against root_heading when it is set.
+    */
+    pub fn solve(&mut self, target: Vec2) -> Solve {
+        let mut solve = Solve {
+            iterations: 0,
+            error: 0.0,
+            reached: false,
+        };
+
+        let Some(tip) = self.joints.last() else {
+            return solve;
+        };
+
+        solve.error = tip.distance(target);
+        if self.joints.len() < 2 || self.lengths.is_empty() {
+            return solve;
+        }
+
+        let root = self.joints[0];
+        let last = self.joints.len() - 1;
+
+        while solve.error > self.tolerance && solve.iterations < self.iterations {
+            self.joints[last] = target;
+            for index in (0..last).rev() {
+                let (next, length) = (self.joints[index + 1], self.lengths[index]);
+                let direction = (self.joints[index] - next).normalize();
+                self.joints[index] = next + direction * length;
+            }
+
+            self.joints[0] = root;
+            let mut prev_heading = self.root_heading;
+            for index in 0..last {
+                let anchor = self.joints[index];
+                let offset = self.joints[index + 1] - anchor;
+
+                let mut heading = match (offset == Vec2::ZERO, prev_heading) {
+                    (true, Some(prev)) => prev,
+                    _ => offset.angle(),
+                };
+
+                let max_bend = self.max_bends.get(index).copied().flatten();
+                if let (Some(prev), Some(max_bend)) = (prev_heading, max_bend) {
+                    heading = prev + wrap_angle(heading - prev).clamp(-max_bend, max_bend);
+                }
+
+                self.joints[index + 1] = anchor + Vec2::from_angle(heading) * self.lengths[index];
+                prev_heading = Some(heading);
+            }
+
+            solve.iterations += 1;
+            solve.error = self.joints[last].distance(target);
+        }
+
+        solve.reached = solve.error <= self.tolerance;
+        solve
+    }
+
+    /*
+        Copies joints after the root onto chain nodes, one per segment.
+        Node headings face back toward the root the same way a chain's
+        face the head, so sides and outlines work unchanged.
+    */
+    pub fn apply_to(&self, nodes: &mut [Node]) {
+        for (index, node) in nodes.iter_mut().enumerate().take(self.lengths.len()) {
+            let (anchor, point) = (self.joints[index], self.joints[index + 1]);
+            node.point = point;
+            if point != anchor {
+                node.theta = segment_heading(&anchor, &point);
+            }
+            node.update_sides();
+        }
+    }
+}
+
+/*
+    Root anchored movement for a chain, the head stays put as the root
+    while the tip reaches, instead of the body following the head.
+*/
+impl Chain {
+    // Solver laid over the head and nodes as they are now, segments keep the chain spacing
+    pub fn fabrik(&self) -> Fabrik {
+        let mut joints = vec![self.head.point];
+        joints.extend(self.head.children.iter().map(|node| node.point));
+
+        let mut fabrik = Fabrik::new(joints);
+        fabrik.lengths.fill(self.spacing());
+        fabrik
+    }
+
+    pub fn reach(&mut self, fabrik: &mut Fabrik, target: Vec2) -> Solve {
+        fabrik.move_root(self.head.point);
+        let solve = fabrik.solve(target);
+        fabrik.apply_to(&mut self.head.children);
+        solve
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+
+    use super::*;
+    use crate::chain::Chain;
+
+    fn arm() -> Fabrik {
+        Fabrik::straight(Vec2::new(0.0, 0.0), 0.0, &[10.0, 10.0, 10.0, 10.0])
+    }
+
+    fn assert_lengths(fabrik: &Fabrik) {
+        for (pair, length) in fabrik.joints().windows(2).zip(fabrik.lengths()) {
+            assert!((pair[0].distance(pair[1]) - length).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn reaches_target_in_range() {
+        let mut fabrik = arm();
+        let target = Vec2::new(15.0, 20.0);
+
+        let solve = fabrik.solve(target);
+        assert!(solve.reached);
+        assert!(fabrik.tip().unwrap().distance(target) <= fabrik.tolerance);
+        assert_eq!(fabrik.root().unwrap(), Vec2::ZERO);
+        assert_lengths(&fabrik);
+    }
+
+    #[test]
+    fn stretches_toward_target_out_of_range() {
+        let mut fabrik = arm();
+        let target = Vec2::new(0.0, 100.0);
+
+        let solve = fabrik.solve(target);
+        assert!(!solve.reached);
+        assert_eq!(solve.iterations, fabrik.iterations);
+        assert_eq!(fabrik.root().unwrap(), Vec2::ZERO);
+        assert_lengths(&fabrik);
+
+        // Straight line pointing at the target
+        assert_eq!(format!("{:.2}", fabrik.tip().unwrap().y), "40.00");
+        assert_eq!(format!("{:.2}", solve.error), "60.00");
+    }
+
+    #[test]
+    fn stops_at_iteration_limit() {
+        let mut fabrik = arm();
+        fabrik.iterations = 1;
+        fabrik.tolerance = 0.0;
+
+        let solve = fabrik.solve(Vec2::new(-10.0, 15.0));
+        assert_eq!(solve.iterations, 1);
+
+        // Already there needs no passes at all
+        let tip = fabrik.tip().unwrap();
+        assert_eq!(fabrik.solve(tip).iterations, 0);
+    }
+
+    #[test]
+    fn bends_stay_within_limit() {
+        let mut fabrik = arm();
+        fabrik.max_bends = vec![Some(PI / 8.0); 4];
+        fabrik.root_heading = Some(0.0);
+        fabrik.iterations = 50;
+
+        fabrik.solve(Vec2::new(-5.0, 5.0));
+        assert_lengths(&fabrik);
+
+        let mut prev = 0.0;
+        for pair in fabrik.joints().windows(2) {
+            let heading = (pair[1] - pair[0]).angle();
+            assert!(wrap_angle(heading - prev).abs() <= PI / 8.0 + 1e-3);
+            prev = heading;
+        }
+    }
+
+    #[test]
+    fn limits_apply_per_joint() {
+        let mut fabrik = arm();
+        fabrik.root_heading = Some(0.0);
+        fabrik.iterations = 50;
+
+        // Only the second joint is held straight, the rest fold freely
+        fabrik.max_bends = vec![None, Some(0.0), None, None];
+        fabrik.solve(Vec2::new(0.0, 20.0));
+        assert_lengths(&fabrik);
+
+        let heading = |index: usize| (fabrik.joints()[index + 1] - fabrik.joints()[index]).angle();
+        assert_eq!(format!("{:.3}", heading(1)), format!("{:.3}", heading(0)));
+        assert!(wrap_angle(heading(2) - heading(1)).abs() > 0.1);
+    }
+
+    #[test]
+    fn too_few_joints_solve_to_nothing() {
+        let mut empty = Fabrik::new(vec![]);
+        let solve = empty.solve(Vec2::new(10.0, 10.0));
+        assert_eq!(solve.iterations, 0);
+        assert!(!solve.reached);
+        empty.move_root(Vec2::new(5.0, 5.0));
+        assert!(empty.joints().is_empty());
+        assert_eq!(empty.root(), None);
+        assert_eq!(empty.tip(), None);
+
+        let mut single = Fabrik::new(vec![Vec2::new(1.0, 1.0)]);
+        let solve = single.solve(Vec2::new(4.0, 5.0));
+        assert_eq!(solve.iterations, 0);
+        assert_eq!(solve.error, 5.0);
+        assert_eq!(single.joints(), &[Vec2::new(1.0, 1.0)]);
+        assert_eq!(single.root(), single.tip());
+    }
+
+    #[test]
+    fn moving_root_keeps_pose() {
+        let mut fabrik = arm();
+        fabrik.solve(Vec2::new(20.0, 20.0));
+        let before = fabrik.clone();
+
+        fabrik.move_root(Vec2::new(5.0, -5.0));
+        for (moved, joint) in fabrik.joints().iter().zip(before.joints()) {
+            assert_eq!(*moved, *joint + Vec2::new(5.0, -5.0));
+        }
+    }
+
+    #[test]
+    fn applies_to_chain_nodes() {
+        let mut chain = Chain::builder()
+            .position(50.0, 50.0)
+            .radials([4.0, 4.0, 4.0, 4.0])
+            .build()
+            .unwrap();
+
+        let mut fabrik = arm();
+        fabrik.solve(Vec2::new(0.0, 30.0));
+        fabrik.apply_to(&mut chain.head.children);
+
+        for (index, node) in chain.head.children.iter().enumerate() {
+            assert_eq!(node.point, fabrik.joints()[index + 1]);
+            let toward_root = (fabrik.joints()[index] - node.point).angle();
+            assert_eq!(format!("{:.3}", node.theta), format!("{toward_root:.3}"));
+        }
+    }
+
+    #[test]
+    fn chain_reaches_with_head_pinned() {
+        let mut chain = Chain::builder()
+            .position(100.0, 100.0)
+            .spacing(10.0)
+            .radials([4.0, 4.0, 4.0])
+            .build()
+            .unwrap();
+
+        let mut fabrik = chain.fabrik();
+        let target = Vec2::new(120.0, 110.0);
+        assert!(chain.reach(&mut fabrik, target).reached);
+
+        assert_eq!(chain.head.point, Vec2::new(100.0, 100.0));
+        let tip = chain.head.children.last().unwrap().point;
+        assert!(tip.distance(target) <= fabrik.tolerance);
+
+        let mut prev = chain.head.point;
+        for node in &chain.head.children {
+            assert!((node.point.distance(prev) - 10.0).abs() < 1e-3);
+            prev = node.point;
+        }
+    }
+}
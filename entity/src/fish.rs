@@ -0,0 +1,417 @@
+use std::f32::consts::PI;
+
+use crate::{
+    chain::{Chain, Eyes, Head},
+    error::ChainError,
+    helper_functions::wrap_angle,
+    vector::Vec2,
+};
+
+// Points around each paired fin
+pub const FIN_SEGMENTS: usize = 12;
+
+/*
+    Pectoral - front pair, out either side of the body
+    Ventral - smaller rear pair, further down the body
+    Dorsal - single fin along the spine, seen from above it
+             bulges out to whichever side the body bends
+*/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FinKind {
+    Pectoral,
+    Ventral,
+    Dorsal,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fin {
+    pub kind: FinKind,
+    pub node: usize, // Index into the head's children the fin grows from
+    pub length: f32, // Along the fin, or along the spine for a dorsal fin
+    pub width: f32,  // Across the fin, or the most a dorsal fin bulges
+    pub sweep: f32,  // Radians a paired fin angles back from straight out
+    pub swing: f32,  // Fin turn per radian of body bend
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TailFin {
+    pub length: f32,
+    pub spread: f32,  // Radians between the two lobes of the fork
+    pub swing: f32,   // Flex per radian of bend across the last nodes
+    pub nodes: usize, // How many of the last nodes the flex follows
+}
+
+#[derive(Debug, Clone)]
+pub struct Fish {
+    pub chain: Chain,
+    pub fins: Vec<Fin>,
+    pub tail: TailFin,
+}
+
+/*
+    Bend at a node, how far its segment heading has turned
+    from the one in front of it. Positive bends to the right.
+
+    bend = wrap( front theta - node theta )
+*/
+fn bend_at(head: &Head, index: usize) -> f32 {
+    let front = match index {
+        0 => head.theta,
+        _ => head.children[index - 1].theta,
+    };
+    wrap_angle(front - head.children[index].theta)
+}
+
+// Ellipse with one end on the anchor, reaching out along theta
+fn fin_ellipse(anchor: Vec2, theta: f32, length: f32, width: f32) -> Vec<Vec2> {
+    let along = Vec2::from_angle(theta);
+    let across = Vec2::from_angle(theta + PI / 2.0);
+    let centre = anchor + along * (length / 2.0);
+
+    (0..FIN_SEGMENTS)
+        .map(|step| {
+            let angle = 2.0 * PI * step as f32 / FIN_SEGMENTS as f32;
+            centre + along * (angle.cos() * length / 2.0) + across * (angle.sin() * width / 2.0)
+        })
+        .collect()
+}
+
+impl Fish {
+    pub fn new(chain: Chain, fins: Vec<Fin>, tail: TailFin) -> Fish {
+        Fish { chain, fins, tail }
+    }
+
+    /*
+        Body widths follow the fish from the reference video at half size,
+        widest just behind the head and tapering off into the tail.
+    */
+    pub fn preset(x: f32, y: f32, width: f32, height: f32) -> Result<Fish, ChainError> {
+        let chain = Chain::builder()
+            .position(x, y)
+            .spacing(32.0)
+            .head_radial(34.0)
+            .radials([40.0, 42.0, 41.0, 38.0, 32.0, 25.0, 19.0, 16.0, 10.0])
+            .eyes(Eyes {
+                size: 0.18,
+                offset: 0.7,
+                angle: PI / 3.0,
+            })
+            .bounds(width, height)
+            .build()?;
+
+        let fins = vec![
+            Fin {
+                kind: FinKind::Pectoral,
+                node: 1,
+                length: 64.0,
+                width: 26.0,
+                sweep: PI / 4.0,
+                swing: 1.5,
+            },
+            Fin {
+                kind: FinKind::Ventral,
+                node: 5,
+                length: 36.0,
+                width: 14.0,
+                sweep: PI / 3.0,
+                swing: 1.5,
+            },
+            Fin {
+                kind: FinKind::Dorsal,
+                node: 2,
+                length: 112.0,
+                width: 24.0,
+                sweep: 0.0,
+                swing: 2.0,
+            },
+        ];
+
+        let tail = TailFin {
+            length: 48.0,
+            spread: PI / 3.0,
+            swing: 1.5,
+            nodes: 3,
+        };
+
+        Ok(Fish::new(chain, fins, tail))
+    }
+
+    pub fn travel(&mut self, dt: f32) {
+        self.chain.travel(dt);
+    }
+
+    pub fn interpolate(&self, next: &Fish, t: f32) -> Fish {
+        Fish {
+            chain: self.chain.interpolate(&next.chain, t),
+            fins: next.fins.clone(),
+            tail: next.tail,
+        }
+    }
+
+    // Fins on nodes the chain does not have are left out
+    pub fn fin_polygons(&self) -> Vec<(FinKind, Vec<Vec2>)> {
+        let mut polygons = vec![];
+
+        for fin in &self.fins {
+            if fin.node >= self.chain.head.children.len() {
+                continue;
+            }
+
+            match fin.kind {
+                FinKind::Pectoral | FinKind::Ventral => {
+                    for polygon in self.paired_fin(fin) {
+                        polygons.push((fin.kind, polygon));
+                    }
+                }
+                FinKind::Dorsal => polygons.push((fin.kind, self.dorsal_fin(fin))),
+            }
+        }
+
+        polygons
+    }
+
+    /*
+        Left of travel is theta - π / 2, sweeping back turns it further
+        toward theta + π. The right fin mirrors it.
+
+        left = theta - π / 2 - sweep + bend * swing
+        right = theta + π / 2 + sweep + bend * swing
+
+        Both turn the same way with the bend, so the fin on the inside
+        of a turn folds into the body while the outer one flares out.
+    */
+    fn paired_fin(&self, fin: &Fin) -> [Vec<Vec2>; 2] {
+        let node = &self.chain.head.children[fin.node];
+        let swing = bend_at(&self.chain.head, fin.node) * fin.swing;
+
+        [
+            fin_ellipse(
+                node.sides.left,
+                node.theta - PI / 2.0 - fin.sweep + swing,
+                fin.length,
+                fin.width,
+            ),
+            fin_ellipse(
+                node.sides.right,
+                node.theta + PI / 2.0 + fin.sweep + swing,
+                fin.length,
+                fin.width,
+            ),
+        ]
+    }
+
+    /*
+        Runs down the spine from the fin node for length, one edge on the
+        spine and the other pushed out by the bend over that stretch.
+
+        bulge = clamp( bend * swing, -1, 1 ) * width * sin( π * s )
+
+        s runs 0..1 along the fin so it meets the spine at both ends.
+        Bending right (positive) throws the fin out to the left.
+    */
+    fn dorsal_fin(&self, fin: &Fin) -> Vec<Vec2> {
+        let nodes = &self.chain.head.children;
+        let spacing = self.chain.spacing().max(f32::EPSILON);
+        let span = ((fin.length / spacing).ceil() as usize).max(1);
+        let last = (fin.node + span).min(nodes.len() - 1);
+
+        let bend: f32 = (fin.node..=last)
+            .map(|index| bend_at(&self.chain.head, index))
+            .sum();
+        let bulge = (bend * fin.swing).clamp(-1.0, 1.0) * fin.width;
+
+        let spine: Vec<_> = nodes[fin.node..=last].iter().collect();
+        let mut polygon: Vec<Vec2> = spine.iter().map(|node| node.point).collect();
+
+        for (step, node) in spine.iter().enumerate().rev() {
+            let s = step as f32 / (spine.len() - 1).max(1) as f32;
+            let out = Vec2::from_angle(node.theta - PI / 2.0);
+            polygon.push(node.point + out * (bulge * (PI * s).sin()));
+        }
+
+        polygon
+    }
+
+    /*
+        Forked tail behind the last node, or the head for an empty chain.
+
+        back = theta + π - bend * swing
+        tips = tail + direction( back ± spread / 2 ) * length
+        notch = tail + direction( back ) * length / 2
+
+        Lagging against the bend makes the tail flick after the body.
+    */
+    pub fn tail_polygon(&self) -> Vec<Vec2> {
+        let head = &self.chain.head;
+        let count = head.children.len();
+
+        let (point, radial, theta) = match head.children.last() {
+            Some(tail) => (tail.point, tail.radial, tail.theta),
+            None => (head.point, head.radial, head.theta),
+        };
+
+        let bend: f32 = (count.saturating_sub(self.tail.nodes)..count)
+            .map(|index| bend_at(head, index))
+            .sum();
+        let back = theta + PI - bend * self.tail.swing;
+
+        let left_root = point + Vec2::from_angle(theta - PI / 2.0) * radial;
+        let right_root = point + Vec2::from_angle(theta + PI / 2.0) * radial;
+
+        vec![
+            left_root,
+            point + Vec2::from_angle(back + self.tail.spread / 2.0) * self.tail.length,
+            point + Vec2::from_angle(back) * (self.tail.length / 2.0),
+            point + Vec2::from_angle(back - self.tail.spread / 2.0) * self.tail.length,
+            right_root,
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn straight_fish() -> Fish {
+        let mut fish = Fish::preset(500.0, 500.0, 1000.0, 1000.0).unwrap();
+        let head = &mut fish.chain.head;
+        head.theta = -PI / 2.0;
+        for node in &mut head.children {
+            node.theta = -PI / 2.0;
+            node.update_sides();
+        }
+        fish
+    }
+
+    fn bend_fish(fish: &mut Fish, bend: f32) {
+        let head = &mut fish.chain.head;
+        let mut theta = head.theta;
+        for node in &mut head.children {
+            theta -= bend;
+            node.theta = theta;
+            node.update_sides();
+        }
+    }
+
+    fn centroid(points: &[Vec2]) -> Vec2 {
+        let mut sum = Vec2::ZERO;
+        for point in points {
+            sum += *point;
+        }
+        sum / points.len() as f32
+    }
+
+    #[test]
+    fn paired_fins_mirror_on_straight_body() {
+        let fish = straight_fish();
+        let polygons = fish.fin_polygons();
+
+        let pectorals: Vec<_> = polygons
+            .iter()
+            .filter(|(kind, _)| *kind == FinKind::Pectoral)
+            .map(|(_, points)| centroid(points))
+            .collect();
+        assert_eq!(pectorals.len(), 2);
+
+        // Heading up the screen, left fin to the left and both swept back
+        let node = &fish.chain.head.children[1];
+        let (left, right) = (pectorals[0], pectorals[1]);
+        assert!(left.x < node.point.x && right.x > node.point.x);
+        assert!(left.y > node.sides.left.y && right.y > node.sides.right.y);
+        assert_eq!(
+            format!("{:.3}", node.point.x - left.x),
+            format!("{:.3}", right.x - node.point.x)
+        );
+    }
+
+    #[test]
+    fn fins_swing_with_bend() {
+        let mut fish = straight_fish();
+        let straight = fish.fin_polygons();
+        bend_fish(&mut fish, 0.1);
+        let bent = fish.fin_polygons();
+
+        for ((kind, before), (_, after)) in straight.iter().zip(&bent) {
+            if *kind == FinKind::Pectoral {
+                let node = &fish.chain.head.children[1];
+                let before_angle = (centroid(before) - node.point).angle();
+                let after_angle = (centroid(after) - node.point).angle();
+                assert_ne!(format!("{before_angle:.3}"), format!("{after_angle:.3}"));
+            }
+        }
+
+        // Dorsal fin lies flat on the spine until the body bends
+        let dorsal = |polygons: &[(FinKind, Vec<Vec2>)]| {
+            polygons
+                .iter()
+                .find(|(kind, _)| *kind == FinKind::Dorsal)
+                .unwrap()
+                .1
+                .clone()
+        };
+        let flat = dorsal(&straight);
+        let spine_x = fish.chain.head.point.x;
+        assert!(flat.iter().all(|point| (point.x - spine_x).abs() < 1e-3));
+
+        let curved = dorsal(&bent);
+        let widest = curved
+            .iter()
+            .map(|point| centroid(&curved).distance(*point))
+            .fold(0.0, f32::max);
+        assert!(widest > 0.0);
+    }
+
+    #[test]
+    fn tail_forks_behind_last_node() {
+        let fish = straight_fish();
+        let tail = fish.tail_polygon();
+        let last = fish.chain.head.children.last().unwrap().point;
+
+        assert_eq!(tail.len(), 5);
+        let (left_tip, notch, right_tip) = (tail[1], tail[2], tail[3]);
+
+        // Heading up, so behind is further down the screen
+        assert!(left_tip.y > notch.y && right_tip.y > notch.y);
+        assert!(notch.y > last.y);
+        assert!(left_tip.x < last.x && right_tip.x > last.x);
+    }
+
+    #[test]
+    fn tail_flexes_against_bend() {
+        let mut fish = straight_fish();
+        let straight_notch = fish.tail_polygon()[2];
+        bend_fish(&mut fish, 0.1);
+
+        let last = fish.chain.head.children.last().unwrap();
+        let notch = fish.tail_polygon()[2];
+        let back = (notch - last.point).angle();
+        let unflexed = wrap_angle(last.theta + PI);
+
+        assert!(wrap_angle(back - unflexed) < 0.0);
+        assert_ne!(straight_notch, notch);
+    }
+
+    #[test]
+    fn missing_nodes_are_skipped() {
+        let chain = Chain::builder()
+            .position(100.0, 100.0)
+            .radials([10.0, 10.0])
+            .build()
+            .unwrap();
+        let preset = Fish::preset(100.0, 100.0, 1000.0, 1000.0).unwrap();
+        let fish = Fish::new(chain, preset.fins.clone(), preset.tail);
+
+        // Only the pectoral pair sits on a node this short chain has
+        let polygons = fish.fin_polygons();
+        assert_eq!(polygons.len(), 2);
+        assert!(polygons.iter().all(|(kind, _)| *kind == FinKind::Pectoral));
+
+        let empty = Fish::new(
+            Chain::builder().build().unwrap(),
+            preset.fins.clone(),
+            preset.tail,
+        );
+        assert!(empty.fin_polygons().is_empty());
+        assert_eq!(empty.tail_polygon().len(), 5);
+    }
+}
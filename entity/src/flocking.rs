@@ -0,0 +1,277 @@
+use crate::{chain::Head, spatial::SpatialGrid, vector::Vec2, world::World};
+
+pub const DEFAULT_VIEW_RADIUS: f32 = 150.0;
+pub const DEFAULT_SEPARATION_RADIUS: f32 = 60.0;
+pub const DEFAULT_SEPARATION: f32 = 1.5;
+pub const DEFAULT_ALIGNMENT: f32 = 1.0;
+pub const DEFAULT_COHESION: f32 = 0.6;
+pub const DEFAULT_GOAL: f32 = 0.8;
+
+/*
+    Weights for the three boid rules, plus a pull toward a shared goal.
+    Neighbours are every other head within view_radius, only those
+    inside separation_radius push away.
+*/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Flocking {
+    pub view_radius: f32,
+    pub separation_radius: f32,
+    pub separation: f32,
+    pub alignment: f32,
+    pub cohesion: f32,
+    pub goal: f32,
+}
+
+impl Default for Flocking {
+    fn default() -> Self {
+        Flocking {
+            view_radius: DEFAULT_VIEW_RADIUS,
+            separation_radius: DEFAULT_SEPARATION_RADIUS,
+            separation: DEFAULT_SEPARATION,
+            alignment: DEFAULT_ALIGNMENT,
+            cohesion: DEFAULT_COHESION,
+            goal: DEFAULT_GOAL,
+        }
+    }
+}
+
+// Where a member of the flock is and which way it faces
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Boid {
+    pub point: Vec2,
+    pub heading: f32,
+}
+
+impl From<&Head> for Boid {
+    fn from(head: &Head) -> Self {
+        Boid {
+            point: head.point,
+            heading: head.theta,
+        }
+    }
+}
+
+impl Flocking {
+    /*
+        Each rule gives a vector of at most unit length, weighted and
+        added onto the current heading.
+
+        separation = Σ normalize( p - q ) * ( 1 - |p - q| / separation_radius )
+        alignment = mean( direction( heading_q ) )
+        cohesion = ( mean( q ) - p ) / view_radius
+        goal = normalize( goal - p )
+
+        desired = angle( direction( heading_p ) + Σ weight * rule )
+
+        A boid with nobody in view and no goal has nothing to say,
+        so it is left to whatever heading it already wanted.
+    */
+    pub fn headings(&self, boids: &[Boid], goal: Option<Vec2>) -> Vec<Option<f32>> {
+        let points: Vec<Vec2> = boids.iter().map(|boid| boid.point).collect();
+        let grid = SpatialGrid::from_points(self.view_radius, &points);
+        let mut neighbours = vec![];
+
+        boids
+            .iter()
+            .enumerate()
+            .map(|(index, boid)| {
+                grid.query_radius(boid.point, self.view_radius, &mut neighbours);
+                neighbours.retain(|other| *other != index);
+
+                if neighbours.is_empty() && goal.is_none() {
+                    return None;
+                }
+
+                let mut steer = Vec2::ZERO;
+
+                if !neighbours.is_empty() {
+                    let mut separation = Vec2::ZERO;
+                    let mut alignment = Vec2::ZERO;
+                    let mut centre = Vec2::ZERO;
+
+                    for other in neighbours.iter().map(|other| &boids[*other]) {
+                        let away = boid.point - other.point;
+                        let distance = away.length();
+                        if distance < self.separation_radius {
+                            separation +=
+                                away.normalize() * (1.0 - distance / self.separation_radius);
+                        }
+
+                        alignment += Vec2::from_angle(other.heading);
+                        centre += other.point;
+                    }
+
+                    let count = neighbours.len() as f32;
+                    let cohesion = (centre / count - boid.point) / self.view_radius;
+
+                    steer += separation * self.separation;
+                    steer += alignment / count * self.alignment;
+                    steer += cohesion * self.cohesion;
+                }
+
+                if let Some(goal) = goal {
+                    steer += (goal - boid.point).normalize() * self.goal;
+                }
+
+                let desired = Vec2::from_angle(boid.heading) + steer;
+                (desired != Vec2::ZERO).then(|| desired.angle())
+            })
+            .collect()
+    }
+}
+
+impl World {
+    /*
+        Points every creature with a chain along the flock's headings,
+        swimming toward goal together if there is one. Called each tick
+        before step, it takes over from any target the heads were seeking.
+    */
+    pub fn flock(&mut self, flocking: &Flocking, goal: Option<Vec2>) {
+        let boids: Vec<Boid> = self
+            .iter()
+            .filter_map(|(_, creature)| creature.chain())
+            .map(|chain| Boid::from(&chain.head))
+            .collect();
+
+        let headings = flocking.headings(&boids, goal);
+        let chains = self
+            .iter_mut()
+            .filter_map(|(_, creature)| creature.chain_mut());
+
+        for (chain, heading) in chains.zip(headings) {
+            chain.head.target = None;
+            if heading.is_some() {
+                chain.head.desired_heading = heading;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+
+    use super::*;
+    use crate::{chain::Chain, octopus::Octopus};
+
+    fn only(rule: impl FnOnce(&mut Flocking)) -> Flocking {
+        let mut flocking = Flocking {
+            separation: 0.0,
+            alignment: 0.0,
+            cohesion: 0.0,
+            goal: 0.0,
+            ..Flocking::default()
+        };
+        rule(&mut flocking);
+        flocking
+    }
+
+    fn boid(x: f32, y: f32, heading: f32) -> Boid {
+        Boid {
+            point: Vec2::new(x, y),
+            heading,
+        }
+    }
+
+    #[test]
+    fn alone_without_a_goal_keeps_its_heading() {
+        let flocking = Flocking::default();
+        let headings = flocking.headings(&[boid(0.0, 0.0, 1.0), boid(1000.0, 0.0, 2.0)], None);
+        assert_eq!(headings, vec![None, None]);
+
+        let headings = flocking.headings(&[boid(0.0, 0.0, 0.0)], Some(Vec2::new(0.0, 100.0)));
+        assert!(headings[0].unwrap() > 0.0);
+    }
+
+    #[test]
+    fn separation_pushes_apart() {
+        let flocking = only(|f| f.separation = 1.0);
+        let headings = flocking.headings(&[boid(0.0, 0.0, 0.0), boid(0.0, 20.0, 0.0)], None);
+
+        // Side by side heading right, the upper one turns up and the lower one down
+        assert!(headings[0].unwrap() < 0.0);
+        assert!(headings[1].unwrap() > 0.0);
+    }
+
+    #[test]
+    fn cohesion_pulls_together() {
+        let flocking = only(|f| f.cohesion = 1.0);
+        let headings = flocking.headings(&[boid(0.0, 0.0, 0.0), boid(0.0, 100.0, 0.0)], None);
+
+        assert!(headings[0].unwrap() > 0.0);
+        assert!(headings[1].unwrap() < 0.0);
+    }
+
+    #[test]
+    fn alignment_matches_neighbours() {
+        let flocking = only(|f| f.alignment = 1.0);
+        let headings = flocking.headings(&[boid(0.0, 0.0, 0.0), boid(50.0, 0.0, PI / 2.0)], None);
+
+        assert_eq!(
+            format!("{:.3}", headings[0].unwrap()),
+            format!("{:.3}", PI / 4.0)
+        );
+        assert_eq!(
+            format!("{:.3}", headings[1].unwrap()),
+            format!("{:.3}", PI / 4.0)
+        );
+    }
+
+    #[test]
+    fn hundreds_of_boids_only_see_their_neighbours() {
+        let boids: Vec<Boid> = (0..400)
+            .map(|index| {
+                boid(
+                    (index % 20) as f32 * 400.0,
+                    (index / 20) as f32 * 400.0,
+                    0.0,
+                )
+            })
+            .collect();
+
+        // Spread wider than the view radius so nobody has company
+        let headings = Flocking::default().headings(&boids, None);
+        assert!(headings.iter().all(Option::is_none));
+    }
+
+    #[test]
+    fn world_flock_steers_chains() {
+        let mut world = World::new();
+        for y in [400.0, 430.0] {
+            let mut chain = Chain::builder()
+                .position(500.0, y)
+                .radials([8.0; 4])
+                .build()
+                .unwrap();
+            chain.seek(Vec2::new(900.0, 900.0));
+            world.add(chain);
+        }
+        world.add(Octopus::preset(800.0, 500.0, 1000.0, 1000.0).unwrap());
+
+        world.flock(&Flocking::default(), None);
+
+        for (_, creature) in world.iter() {
+            if let Some(chain) = creature.chain() {
+                assert_eq!(chain.head.target, None);
+                assert!(chain.head.desired_heading.is_some());
+            }
+        }
+
+        let before = world.clone();
+        for _ in 0..30 {
+            world.flock(&Flocking::default(), None);
+            world.step(1.0 / 30.0);
+        }
+
+        // Too close at the start, they spread out
+        let gap = |world: &World| {
+            let heads: Vec<Vec2> = world
+                .iter()
+                .filter_map(|(_, c)| c.chain())
+                .map(|c| c.head.point)
+                .collect();
+            heads[0].distance(heads[1])
+        };
+        assert!(gap(&world) > gap(&before));
+    }
+}
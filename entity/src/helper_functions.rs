@@ -1,14 +1,8 @@
-use std::fmt::Display;
+use std::f32::consts::{PI, TAU};
 
-use crate::chain::Node;
+use crate::{chain::Node, vector::Vec2};
 
-#[derive(Default, Clone, Debug)]
-pub struct Point {
-    pub x: f32,
-    pub y: f32,
-}
-
-// pub fn point_bounding_rect(point: &Point, radius: &f64) -> [f64; 4] {
+// pub fn point_bounding_rect(point: &Vec2, radius: &f64) -> [f64; 4] {
 //     [
 //         point.x as f64 - radius,
 //         point.y as f64 - radius,
@@ -17,7 +11,7 @@ pub struct Point {
 //     ]
 // }
 
-pub fn point_bounding_rect(point: &Point, radius: &f64) -> [f64; 4] {
+pub fn point_bounding_rect(point: &Vec2, radius: &f64) -> [f64; 4] {
     [
         point.x as f64 - radius,
         point.y as f64 - radius,
@@ -26,74 +20,139 @@ pub fn point_bounding_rect(point: &Point, radius: &f64) -> [f64; 4] {
     ]
 }
 
-pub fn line_between_points_bounds(point_a: &Point, point_b: &Point) -> [f64; 4] {
+pub fn line_between_points_bounds(point_a: &Vec2, point_b: &Vec2) -> [f64; 4] {
     [
-        point_a.x.clone() as f64,
-        point_a.y.clone() as f64,
-        point_b.x.clone() as f64,
-        point_b.y.clone() as f64,
+        point_a.x as f64,
+        point_a.y as f64,
+        point_b.x as f64,
+        point_b.y as f64,
     ]
 }
 
-impl Display for Point {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "({}, {})", self.x, self.y)
-    }
+pub fn polygon_points(points: &[Vec2]) -> Vec<[f64; 2]> {
+    points
+        .iter()
+        .map(|point| [point.x as f64, point.y as f64])
+        .collect()
 }
 
-pub fn pull_node_on_point(anchor: &Point, mut mover: Node, radius: &f32) -> Node {
-    match move_point(anchor, &mover.point, radius) {
-        Some(new_pos) => {
-            mover.point = new_pos;
-        }
-        None => {
-            return mover;
-        }
+pub fn pull_node_on_point(anchor: &Vec2, anchor_heading: &f32, mover: &mut Node, radius: &f32) {
+    let pulled = move_point(anchor, &mover.point, radius);
+    let bent = constrain_joint(
+        anchor,
+        anchor_heading,
+        pulled.as_ref().unwrap_or(&mover.point),
+        &mover.max_bend,
+    );
+
+    if let (_, Some(new_pos)) | (Some(new_pos), None) = (pulled, bent) {
+        mover.point = new_pos;
     }
 
-    mover.theta = get_point_heading(anchor, &mover.point);
+    // Heading follows the anchor even when the node is left in place,
+    // unless both sit on the same spot and there is nothing to face
+    if mover.point != *anchor {
+        mover.theta = segment_heading(anchor, &mover.point);
+    }
     mover.update_sides();
+}
+
+/*
+    Heading of the segment running from mover up to anchor,
+    this is the direction the mover would travel to reach the anchor.
+
+    theta = angle( anchor - mover )
+
+    The angle comes from atan2 which keeps the quadrant, so moving left gives ±π
+    rather than 0 and vertically aligned points give ±π / 2 instead of dividing by 0.
+*/
+pub fn segment_heading(anchor: &Vec2, mover: &Vec2) -> f32 {
+    (*anchor - *mover).angle()
+}
+
+/*
+    Keeps an angle within -π..=π
+
+    wrapped = ( theta + π ) mod 2π - π
+
+    Angles already in range are returned as they are, the shift by π
+    would cost them a little precision. An infinite or NaN angle has no
+    direction to keep and comes back as 0 rather than spreading NaN.
+*/
+pub fn wrap_angle(theta: f32) -> f32 {
+    if !theta.is_finite() {
+        return 0.0;
+    }
+    if (-PI..=PI).contains(&theta) {
+        return theta;
+    }
 
-    mover
+    (theta + PI).rem_euclid(TAU) - PI
 }
 
 /*
-    dx = mover_x - anchor_x
-    dy = mover_y - anchor_y
+    Turns from a toward b the short way round.
+    t = 0 gives a, t = 1 gives b.
+*/
+pub fn lerp_angle(a: f32, b: f32, t: f32) -> f32 {
+    wrap_angle(a + wrap_angle(b - a) * t)
+}
+
+/*
+    anchor_heading = heading of the segment in front of the joint
+    heading = segment_heading( anchor, mover )
+
+    bend = heading - anchor_heading, wrapped to -π..=π
+
+    if |bend| <= max_bend no need to move
+
+    heading = anchor_heading + clamp( bend, -max_bend, max_bend )
 
-    theta = arctan( dy / dx )
+    new = anchor - ( dist * direction(heading) )
 */
-fn get_point_heading(anchor: &Point, mover: &Point) -> f32 {
-    let dx = anchor.x - mover.x;
-    let dy = anchor.y - mover.y;
+pub fn constrain_joint(
+    anchor: &Vec2,
+    anchor_heading: &f32,
+    mover: &Vec2,
+    max_bend: &f32,
+) -> Option<Vec2> {
+    let distance = anchor.distance(*mover);
+
+    // Nodes on top of each other have no segment to bend
+    if distance == 0.0 {
+        return None;
+    }
+
+    let bend = wrap_angle(segment_heading(anchor, mover) - anchor_heading);
 
-    let base_theta = (dy / dx).atan();
+    // Joint is already within its limit so can return no new pos
+    if &bend.abs() <= max_bend {
+        return None;
+    }
 
-    return base_theta;
+    let heading = anchor_heading + bend.clamp(-max_bend, *max_bend);
+
+    Some(*anchor - Vec2::from_angle(heading) * distance)
 }
 
 /*
     prev = anchor point
     curr = moving point
 
-    dx = prev_x - curr_x
-    dy = prev_y - curr_y
+    offset = curr - prev
 
-    dist = sqrt( dy^2 + dx^2 )
+    dist = length( offset )
 
     if dist <= radius no need to move
 
     percent_change = radius / dist
 
-    new_x = prev_x + ( dx * percent_change )
-    new_y = prev_y + ( dy * percent_change )
+    new = prev + ( offset * percent_change )
 
 */
-pub fn move_point(anchor: &Point, mover: &Point, radius: &f32) -> Option<Point> {
-    let dx = mover.x - anchor.x;
-    let dy = mover.y - anchor.y;
-
-    let distance = (dx.powi(2) + dy.powi(2)).sqrt();
+pub fn move_point(anchor: &Vec2, mover: &Vec2, radius: &f32) -> Option<Vec2> {
+    let offset = *mover - *anchor;
+    let distance = offset.length();
 
     // Node is already in radius so can return no new pos
     if &distance <= radius {
@@ -102,10 +161,7 @@ pub fn move_point(anchor: &Point, mover: &Point, radius: &f32) -> Option<Point>
 
     let percent_diff = radius / distance;
 
-    Some(Point {
-        x: anchor.x + (dx * percent_diff),
-        y: anchor.y + (dy * percent_diff),
-    })
+    Some(*anchor + offset * percent_diff)
 }
 
 #[cfg(test)]
@@ -114,8 +170,8 @@ mod tests {
 
     #[test]
     fn pull_direction_x() {
-        let anchor = Point { x: 10f32, y: 0f32 };
-        let mover = Point { x: 3f32, y: 0f32 };
+        let anchor = Vec2 { x: 10f32, y: 0f32 };
+        let mover = Vec2 { x: 3f32, y: 0f32 };
         let result = move_point(&anchor, &mover, &5f32);
         assert!(result.is_some());
         let result = result.unwrap();
@@ -126,8 +182,8 @@ mod tests {
     #[test]
     fn pull_direction_y() {
         {
-            let anchor = Point { x: 0f32, y: 10f32 };
-            let mover = Point { x: 0f32, y: 3f32 };
+            let anchor = Vec2 { x: 0f32, y: 10f32 };
+            let mover = Vec2 { x: 0f32, y: 3f32 };
             let result = move_point(&anchor, &mover, &5f32);
             assert!(result.is_some());
             let result = result.unwrap();
@@ -138,8 +194,8 @@ mod tests {
 
     #[test]
     fn pull_at_angle() {
-        let anchor = Point { x: 3.0, y: 3.0 };
-        let mover = Point { x: 0.0, y: 5.0 };
+        let anchor = Vec2 { x: 3.0, y: 3.0 };
+        let mover = Vec2 { x: 0.0, y: 5.0 };
 
         let radius = 1.8;
 
@@ -152,12 +208,130 @@ mod tests {
 
     #[test]
     fn no_move_need() {
-        let anchor = Point { x: 3.0, y: 3.0 };
-        let mover = Point { x: 0.0, y: 5.0 };
+        let anchor = Vec2 { x: 3.0, y: 3.0 };
+        let mover = Vec2 { x: 0.0, y: 5.0 };
 
         let radius = 100.0;
 
         let result = move_point(&anchor, &mover, &radius);
         assert!(result.is_none());
     }
+
+    #[test]
+    fn heading_in_every_quadrant() {
+        let anchor = Vec2 { x: 0.0, y: 0.0 };
+
+        // Mover behind the anchor for each travel direction
+        let cases = [
+            (Vec2 { x: -1.0, y: 0.0 }, 0.0),
+            (Vec2 { x: 0.0, y: -1.0 }, PI / 2.0),
+            (Vec2 { x: 1.0, y: 0.0 }, PI),
+            (Vec2 { x: 0.0, y: 1.0 }, -PI / 2.0),
+            (Vec2 { x: 1.0, y: 1.0 }, -3.0 * PI / 4.0),
+            (Vec2 { x: -1.0, y: -1.0 }, PI / 4.0),
+        ];
+
+        for (mover, expected) in cases {
+            assert_eq!(
+                format!("{:.3}", segment_heading(&anchor, &mover)),
+                format!("{:.3}", expected),
+                "Mover at {mover}"
+            );
+        }
+    }
+
+    #[test]
+    fn heading_sweep_matches_direction() {
+        let anchor = Vec2 { x: 50.0, y: 50.0 };
+
+        for degree in 0..360 {
+            let theta = (degree as f32).to_radians();
+            let mover = anchor - Vec2::from_angle(theta) * 10.0;
+
+            let heading = segment_heading(&anchor, &mover);
+            assert!(
+                wrap_angle(heading - theta).abs() < 1e-3,
+                "Heading {heading} for direction {theta}"
+            );
+        }
+    }
+
+    #[test]
+    fn wrap_angle_range() {
+        assert_eq!(format!("{:.3}", wrap_angle(3.0 * PI / 2.0)), "-1.571");
+        assert_eq!(format!("{:.3}", wrap_angle(-3.0 * PI / 2.0)), "1.571");
+        assert_eq!(format!("{:.3}", wrap_angle(5.0 * PI)), "3.142");
+        assert_eq!(wrap_angle(0.5), 0.5);
+    }
+
+    #[test]
+    fn wrap_angle_huge_input() {
+        // Finishes at once however far out theta is
+        let wrapped = wrap_angle(1e9);
+        assert!((-PI..=PI).contains(&wrapped));
+        assert!((-PI..=PI).contains(&wrap_angle(-3.4e38)));
+    }
+
+    #[test]
+    fn wrap_angle_non_finite_is_zero() {
+        assert_eq!(wrap_angle(f32::INFINITY), 0.0);
+        assert_eq!(wrap_angle(f32::NEG_INFINITY), 0.0);
+        assert_eq!(wrap_angle(f32::NAN), 0.0);
+        // A bad target leaves the angle where it was
+        assert_eq!(lerp_angle(0.5, f32::INFINITY, 1.0), 0.5);
+    }
+
+    #[test]
+    fn lerp_angle_short_way() {
+        assert_eq!(lerp_angle(0.0, 1.0, 0.5), 0.5);
+
+        // Halfway from just above to just below π passes through π
+        let halfway = lerp_angle(3.0 * PI / 4.0, -3.0 * PI / 4.0, 0.5);
+        assert_eq!(format!("{:.3}", halfway.abs()), format!("{:.3}", PI));
+
+        assert_eq!(format!("{:.3}", lerp_angle(0.2, -0.4, 1.0)), "-0.400");
+    }
+
+    #[test]
+    fn joint_within_limit() {
+        // Mover sits directly behind an anchor heading right
+        let anchor = Vec2 { x: 10.0, y: 0.0 };
+        let mover = Vec2 { x: 0.0, y: 1.0 };
+
+        let result = constrain_joint(&anchor, &0.0, &mover, &(PI / 4.0));
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn joint_bent_past_limit() {
+        // Mover is folded straight up from an anchor heading right
+        let anchor = Vec2 { x: 0.0, y: 0.0 };
+        let mover = Vec2 { x: 0.0, y: -10.0 };
+
+        let result = constrain_joint(&anchor, &0.0, &mover, &(PI / 4.0));
+        assert!(result.is_some());
+        let result = result.unwrap();
+        assert_eq!(format!("{:.3}", result.x), "-7.071");
+        assert_eq!(format!("{:.3}", result.y), "-7.071");
+
+        // Same fold on the other side
+        let mover = Vec2 { x: 0.0, y: 10.0 };
+        let result = constrain_joint(&anchor, &0.0, &mover, &(PI / 4.0)).unwrap();
+        assert_eq!(format!("{:.3}", result.x), "-7.071");
+        assert_eq!(format!("{:.3}", result.y), "7.071");
+    }
+
+    #[test]
+    fn joint_bend_across_pi() {
+        // Anchor heading left, mover slightly below and ahead of it
+        let anchor = Vec2 { x: 0.0, y: 0.0 };
+        let mover = Vec2 { x: -5.0, y: 5.0 };
+
+        let result = constrain_joint(&anchor, &PI, &mover, &(PI / 8.0)).unwrap();
+        let bend = wrap_angle(segment_heading(&anchor, &result) - PI);
+        assert_eq!(format!("{:.3}", bend.abs()), format!("{:.3}", PI / 8.0));
+
+        let distance = result.length();
+        assert_eq!(format!("{:.3}", distance), format!("{:.3}", 50f32.sqrt()));
+    }
 }
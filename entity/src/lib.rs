@@ -1,5 +1,23 @@
+pub mod arena;
 pub mod chain;
+pub mod creature;
+pub mod error;
+pub mod fabrik;
+pub mod fish;
+pub mod flocking;
 mod helper_functions;
+pub mod limb;
+pub mod lizard;
+pub mod octopus;
+pub mod outline;
+pub mod render;
+pub mod skeleton;
+pub mod spatial;
+pub mod spline;
+pub mod svg;
+pub mod vector;
+pub mod world;
 
 pub use helper_functions::line_between_points_bounds;
 pub use helper_functions::point_bounding_rect;
+pub use helper_functions::polygon_points;
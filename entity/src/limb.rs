@@ -0,0 +1,408 @@
+use std::f32::consts::PI;
+
+use crate::{
+    chain::{Head, Node},
+    vector::Vec2,
+};
+
+pub const DEFAULT_REST_ANGLE: f32 = PI / 3.0;
+pub const DEFAULT_STEP_TIME: f32 = 0.15;
+
+// How far past the rest target a step lands, as a fraction of step distance
+const STEP_OVERSHOOT: f32 = 0.5;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+// A foot in the air, moving from where it lifted to where it will land
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Step {
+    from: Vec2,
+    to: Vec2,
+    elapsed: f32,
+}
+
+/*
+    Two segment leg hanging off the side of a body node.
+
+    The foot stays planted on the ground while the body moves over it,
+    once it drifts too far from its rest target it lifts and steps
+    to just past the target, the way the body is heading. Legs in the
+    same gait group step together, a group only lifts while every
+    other group is planted.
+
+    A leg whose node the chain does not have is left where it is.
+*/
+#[derive(Debug, Clone)]
+pub struct Leg {
+    pub node: usize, // Index into the head's children the hip sits on
+    pub side: Side,
+    pub upper: f32,
+    pub lower: f32,
+    pub rest_angle: f32,    // Radians from the heading toward the rest target
+    pub rest_reach: f32,    // Distance from the node to the rest target
+    pub step_distance: f32, // Drift from the rest target that starts a step
+    pub step_time: f32,     // Seconds a step takes
+    pub group: usize,       // Gait group, legs in one group step together
+    pub foot: Vec2,
+    pub knee: Vec2,
+    step: Option<Step>,
+}
+
+/*
+    Knee for a two bone limb from the law of cosines.
+
+    distance = clamp( |target - root|, |upper - lower|, upper + lower )
+    cos( hip ) = ( upper² + distance² - lower² ) / ( 2 * upper * distance )
+
+    The knee is upper along the line to the target turned by hip,
+    either way round, whichever lands further from away_from.
+    Out of reach targets straighten the leg toward them.
+*/
+pub fn solve_two_bone(root: Vec2, target: Vec2, upper: f32, lower: f32, away_from: Vec2) -> Vec2 {
+    let offset = target - root;
+    let distance = offset
+        .length()
+        .clamp((upper - lower).abs(), upper + lower)
+        .max(f32::EPSILON);
+
+    let cos_hip = (upper * upper + distance * distance - lower * lower) / (2.0 * upper * distance);
+    let hip = cos_hip.clamp(-1.0, 1.0).acos();
+
+    let base = offset.angle();
+    let [first, second] =
+        [base + hip, base - hip].map(|theta| root + Vec2::from_angle(theta) * upper);
+
+    if first.distance(away_from) >= second.distance(away_from) {
+        first
+    } else {
+        second
+    }
+}
+
+impl Leg {
+    pub fn new(node: usize, side: Side, upper: f32, lower: f32, group: usize) -> Leg {
+        let reach = upper + lower;
+        Leg {
+            node,
+            side,
+            upper,
+            lower,
+            rest_angle: DEFAULT_REST_ANGLE,
+            rest_reach: reach,
+            step_distance: reach * 0.6,
+            step_time: DEFAULT_STEP_TIME,
+            group,
+            foot: Vec2::ZERO,
+            knee: Vec2::ZERO,
+            step: None,
+        }
+    }
+
+    fn body<'a>(&self, head: &'a Head) -> Option<&'a Node> {
+        head.children.get(self.node)
+    }
+
+    pub fn hip(&self, head: &Head) -> Option<Vec2> {
+        let node = self.body(head)?;
+        Some(match self.side {
+            Side::Left => node.sides.left,
+            Side::Right => node.sides.right,
+        })
+    }
+
+    /*
+        Left of travel is theta - π / 2 so the left rest target
+        turns from the heading that way, the right one mirrors it.
+
+        target = point + rest_reach * direction( theta ∓ rest_angle )
+    */
+    pub fn rest_target(&self, head: &Head) -> Option<Vec2> {
+        let node = self.body(head)?;
+        let theta = match self.side {
+            Side::Left => node.theta - self.rest_angle,
+            Side::Right => node.theta + self.rest_angle,
+        };
+        Some(node.point + Vec2::from_angle(theta) * self.rest_reach)
+    }
+
+    pub fn is_stepping(&self) -> bool {
+        self.step.is_some()
+    }
+
+    // Puts the foot straight down on its rest target
+    pub fn plant(&mut self, head: &Head) {
+        let Some(rest) = self.rest_target(head) else {
+            return;
+        };
+
+        self.foot = rest;
+        self.step = None;
+        self.solve(head);
+    }
+
+    /*
+        The foot is held within the leg's reach of the hip, dragged
+        along when the body pulls away faster than it can step.
+
+        foot = hip + clamp( |foot - hip|, |upper - lower|, upper + lower ) * direction
+    */
+    pub fn solve(&mut self, head: &Head) {
+        let (Some(hip), Some(body)) = (self.hip(head), self.body(head)) else {
+            return;
+        };
+
+        let offset = self.foot - hip;
+        let length = offset.length();
+        let reach = length.clamp((self.upper - self.lower).abs(), self.upper + self.lower);
+        if length > 0.0 && reach != length {
+            self.foot = hip + offset * (reach / length);
+        }
+
+        self.knee = solve_two_bone(hip, self.foot, self.upper, self.lower, body.point);
+    }
+
+    fn drift(&self, head: &Head) -> Option<f32> {
+        Some(self.foot.distance(self.rest_target(head)?))
+    }
+
+    fn needs_step(&self, head: &Head) -> bool {
+        self.step.is_none()
+            && self
+                .drift(head)
+                .is_some_and(|drift| drift > self.step_distance)
+    }
+
+    fn start_step(&mut self, head: &Head) {
+        let Some(rest) = self.rest_target(head) else {
+            return;
+        };
+        let ahead = (rest - self.foot).normalize() * (self.step_distance * STEP_OVERSHOOT);
+        self.step = Some(Step {
+            from: self.foot,
+            to: rest + ahead,
+            elapsed: 0.0,
+        });
+    }
+
+    /*
+        Eases the foot along the step so it lifts and lands gently.
+
+        s = t² * ( 3 - 2t )
+    */
+    fn advance_step(&mut self, dt: f32) {
+        let Some(step) = &mut self.step else {
+            return;
+        };
+
+        step.elapsed += dt;
+        let t = (step.elapsed / self.step_time.max(f32::EPSILON)).min(1.0);
+        self.foot = step.from.lerp(step.to, t * t * (3.0 - 2.0 * t));
+
+        if t >= 1.0 {
+            self.step = None;
+        }
+    }
+}
+
+/*
+    Moves every leg on by dt, called after the body has moved.
+
+    Steps already under way carry on first. Then legs that need a step
+    take one, a group at a time, furthest drifted group first, and only
+    while no other group has a foot in the air.
+*/
+pub fn update_legs(legs: &mut [Leg], head: &Head, dt: f32) {
+    for leg in legs.iter_mut() {
+        let Some(hip) = leg.hip(head) else {
+            continue;
+        };
+
+        // Wrapping round the arena leaves feet a whole arena behind
+        if leg.foot.distance(hip) > (leg.upper + leg.lower) * 4.0 {
+            leg.plant(head);
+        }
+
+        leg.advance_step(dt);
+    }
+
+    let mut waiting: Option<(usize, f32)> = None;
+    for leg in legs.iter() {
+        if let (true, Some(drift)) = (leg.needs_step(head), leg.drift(head)) {
+            let drift = drift - leg.step_distance;
+            if waiting.is_none_or(|(_, most)| drift > most) {
+                waiting = Some((leg.group, drift));
+            }
+        }
+    }
+
+    if let Some((group, _)) = waiting {
+        let other_lifted = legs
+            .iter()
+            .any(|leg| leg.group != group && leg.is_stepping());
+
+        if !other_lifted {
+            for leg in legs.iter_mut() {
+                if leg.group == group && leg.needs_step(head) {
+                    leg.start_step(head);
+                }
+            }
+        }
+    }
+
+    for leg in legs.iter_mut() {
+        leg.solve(head);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::Chain;
+
+    fn body() -> Chain {
+        let mut chain = Chain::builder()
+            .position(500.0, 500.0)
+            .spacing(20.0)
+            .radials([10.0, 10.0, 10.0])
+            .build()
+            .unwrap();
+        chain.head.theta = PI / 2.0;
+        chain.head.speed = 120.0;
+        chain.head.desired_heading = Some(PI / 2.0);
+
+        // Let the body straighten out behind the head
+        for _ in 0..30 {
+            chain.travel(1.0 / 60.0);
+        }
+        chain
+    }
+
+    #[test]
+    fn two_bone_keeps_lengths() {
+        let root = Vec2::new(0.0, 0.0);
+        let target = Vec2::new(12.0, 5.0);
+        let knee = solve_two_bone(root, target, 10.0, 8.0, Vec2::new(0.0, 10.0));
+
+        assert_eq!(format!("{:.3}", knee.distance(root)), "10.000");
+        assert_eq!(format!("{:.3}", knee.distance(target)), "8.000");
+
+        // Knee bends away from the given point
+        assert!(knee.y < 0.0);
+    }
+
+    #[test]
+    fn two_bone_straightens_out_of_reach() {
+        let knee = solve_two_bone(
+            Vec2::ZERO,
+            Vec2::new(100.0, 0.0),
+            10.0,
+            8.0,
+            Vec2::new(0.0, 10.0),
+        );
+        assert_eq!(format!("{:.3}", knee.x), "10.000");
+        assert_eq!(format!("{:.3}", knee.y.abs()), "0.000");
+    }
+
+    #[test]
+    fn feet_stay_planted_until_drifting() {
+        let mut chain = body();
+        let mut leg = Leg::new(0, Side::Left, 12.0, 12.0, 0);
+        leg.plant(&chain.head);
+        let planted = leg.foot;
+
+        // A couple of small moves leaves the foot where it was
+        let mut legs = [leg];
+        for _ in 0..2 {
+            chain.travel(1.0 / 60.0);
+            update_legs(&mut legs, &chain.head, 1.0 / 60.0);
+        }
+        assert_eq!(legs[0].foot, planted);
+        assert!(!legs[0].is_stepping());
+
+        // Walking on far enough lifts it
+        let mut stepped = false;
+        for _ in 0..60 {
+            chain.travel(1.0 / 60.0);
+            update_legs(&mut legs, &chain.head, 1.0 / 60.0);
+            stepped |= legs[0].is_stepping();
+        }
+        assert!(stepped);
+        assert!(legs[0].foot.y > planted.y);
+    }
+
+    #[test]
+    fn gait_groups_alternate() {
+        let mut chain = body();
+        let mut legs = [
+            Leg::new(0, Side::Left, 12.0, 12.0, 0),
+            Leg::new(0, Side::Right, 12.0, 12.0, 1),
+            Leg::new(2, Side::Left, 12.0, 12.0, 1),
+            Leg::new(2, Side::Right, 12.0, 12.0, 0),
+        ];
+        for leg in &mut legs {
+            leg.plant(&chain.head);
+        }
+
+        let mut lifted_groups = vec![];
+        for _ in 0..240 {
+            chain.travel(1.0 / 60.0);
+            update_legs(&mut legs, &chain.head, 1.0 / 60.0);
+
+            let zero = legs.iter().any(|leg| leg.group == 0 && leg.is_stepping());
+            let one = legs.iter().any(|leg| leg.group == 1 && leg.is_stepping());
+            assert!(!(zero && one), "Both groups lifted at once");
+
+            let lifted = if zero {
+                Some(0)
+            } else if one {
+                Some(1)
+            } else {
+                None
+            };
+            if let Some(group) = lifted {
+                if lifted_groups.last() != Some(&group) {
+                    lifted_groups.push(group);
+                }
+            }
+        }
+
+        // Each group gets a turn
+        assert!(lifted_groups.contains(&0) && lifted_groups.contains(&1));
+    }
+
+    #[test]
+    fn legs_on_missing_nodes_are_ignored() {
+        let chain = Chain::builder().position(50.0, 50.0).build().unwrap();
+        let mut legs = [Leg::new(3, Side::Right, 5.0, 5.0, 0)];
+        update_legs(&mut legs, &chain.head, 0.1);
+        assert_eq!(legs[0].foot, Vec2::ZERO);
+
+        assert_eq!(legs[0].hip(&chain.head), None);
+        assert_eq!(legs[0].rest_target(&chain.head), None);
+        legs[0].plant(&chain.head);
+        legs[0].solve(&chain.head);
+        assert_eq!(legs[0].foot, Vec2::ZERO);
+        assert_eq!(legs[0].knee, Vec2::ZERO);
+    }
+
+    #[test]
+    fn solve_keeps_foot_within_reach() {
+        let chain = body();
+        let mut leg = Leg::new(1, Side::Right, 12.0, 8.0, 0);
+        let hip = leg.hip(&chain.head).unwrap();
+
+        leg.foot = hip + Vec2::new(100.0, 0.0);
+        leg.solve(&chain.head);
+        assert_eq!(format!("{:.3}", leg.foot.distance(hip)), "20.000");
+        assert_eq!(format!("{:.3}", leg.knee.distance(leg.foot)), "8.000");
+
+        leg.foot = hip + Vec2::new(1.0, 0.0);
+        leg.solve(&chain.head);
+        assert_eq!(format!("{:.3}", leg.foot.distance(hip)), "4.000");
+        assert_eq!(format!("{:.3}", leg.knee.distance(leg.foot)), "8.000");
+    }
+}
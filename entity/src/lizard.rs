@@ -0,0 +1,149 @@
+use std::f32::consts::PI;
+
+use crate::{
+    chain::{Chain, Eyes},
+    error::ChainError,
+    limb::{update_legs, Leg, Side},
+};
+
+#[derive(Debug, Clone)]
+pub struct Lizard {
+    pub chain: Chain,
+    pub legs: Vec<Leg>,
+}
+
+impl Lizard {
+    // Plants every foot on its rest target to start
+    pub fn new(chain: Chain, legs: Vec<Leg>) -> Lizard {
+        let mut lizard = Lizard { chain, legs };
+        lizard.plant();
+        lizard
+    }
+
+    /*
+        Narrow neck, wide shoulders and hips and a long thin tail.
+
+        Front left steps with back right and front right with back left,
+        the diagonal gait a real lizard walks with.
+    */
+    pub fn preset(x: f32, y: f32, width: f32, height: f32) -> Result<Lizard, ChainError> {
+        let mut chain = Chain::builder()
+            .position(x, y)
+            .spacing(24.0)
+            .head_radial(24.0)
+            .radials([
+                16.0, 26.0, 30.0, 28.0, 26.0, 30.0, 24.0, 14.0, 10.0, 8.0, 6.0, 5.0, 4.0, 4.0,
+            ])
+            .max_bends(vec![PI / 10.0; 14])
+            .eyes(Eyes {
+                size: 0.2,
+                offset: 0.75,
+                angle: 2.0 * PI / 5.0,
+            })
+            .bounds(width, height)
+            .build()?;
+        chain.head.speed = 160.0;
+
+        let legs = vec![
+            Leg::new(2, Side::Left, 28.0, 24.0, 0),
+            Leg::new(2, Side::Right, 28.0, 24.0, 1),
+            Leg::new(6, Side::Left, 24.0, 22.0, 1),
+            Leg::new(6, Side::Right, 24.0, 22.0, 0),
+        ];
+
+        Ok(Lizard::new(chain, legs))
+    }
+
+    pub fn plant(&mut self) {
+        for leg in &mut self.legs {
+            leg.plant(&self.chain.head);
+        }
+    }
+
+    pub fn travel(&mut self, dt: f32) {
+        self.chain.travel(dt);
+        update_legs(&mut self.legs, &self.chain.head, dt);
+    }
+
+    /*
+        Feet blend like the body does, knees are solved again
+        against the blended hips so the legs keep their lengths.
+    */
+    pub fn interpolate(&self, next: &Lizard, t: f32) -> Lizard {
+        let chain = self.chain.interpolate(&next.chain, t);
+        let mut legs = next.legs.clone();
+
+        for (leg, prev) in legs.iter_mut().zip(&self.legs) {
+            if prev.foot.distance(leg.foot) < (leg.upper + leg.lower) * 2.0 {
+                leg.foot = prev.foot.lerp(leg.foot, t);
+            }
+            leg.solve(&chain.head);
+        }
+
+        Lizard { chain, legs }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vector::Vec2;
+
+    #[test]
+    fn legs_keep_their_lengths_while_walking() {
+        let mut lizard = Lizard::preset(300.0, 300.0, 1000.0, 1000.0).unwrap();
+        lizard.chain.seek(Vec2::new(800.0, 700.0));
+
+        for _ in 0..180 {
+            lizard.travel(1.0 / 60.0);
+
+            for leg in &lizard.legs {
+                let hip = leg.hip(&lizard.chain.head).unwrap();
+                assert!((leg.knee.distance(hip) - leg.upper).abs() < 1e-2);
+
+                // Feet are dragged along rather than left out of reach
+                assert!((leg.knee.distance(leg.foot) - leg.lower).abs() < 1e-2);
+            }
+        }
+    }
+
+    #[test]
+    fn walking_moves_every_foot() {
+        let mut lizard = Lizard::preset(300.0, 300.0, 1000.0, 1000.0).unwrap();
+        lizard.chain.head.speed = 120.0;
+
+        // Let the body straighten out before planting
+        lizard.chain.seek(Vec2::new(800.0, 300.0));
+        for _ in 0..60 {
+            lizard.travel(1.0 / 60.0);
+        }
+        lizard.plant();
+        let start: Vec<Vec2> = lizard.legs.iter().map(|leg| leg.foot).collect();
+
+        for _ in 0..120 {
+            lizard.travel(1.0 / 60.0);
+        }
+
+        for (leg, start) in lizard.legs.iter().zip(start) {
+            assert!(leg.foot.x > start.x + 20.0, "{} from {}", leg.foot, start);
+        }
+    }
+
+    #[test]
+    fn interpolate_keeps_legs_attached() {
+        let mut lizard = Lizard::preset(300.0, 300.0, 1000.0, 1000.0).unwrap();
+        lizard.chain.seek(Vec2::new(800.0, 700.0));
+        for _ in 0..30 {
+            lizard.travel(1.0 / 60.0);
+        }
+
+        let prev = lizard.clone();
+        lizard.travel(1.0 / 60.0);
+        let blended = prev.interpolate(&lizard, 0.5);
+
+        for leg in &blended.legs {
+            let hip = leg.hip(&blended.chain.head).unwrap();
+            assert!((leg.knee.distance(hip) - leg.upper).abs() < 1e-2);
+        }
+    }
+}
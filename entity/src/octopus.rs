@@ -0,0 +1,365 @@
+use std::f32::consts::PI;
+
+use crate::{
+    chain::{Chain, Eyes},
+    error::ChainError,
+    fabrik::Fabrik,
+    vector::Vec2,
+};
+
+pub const DEFAULT_REACH_SPEED: f32 = 180.0;
+pub const DEFAULT_STIFFNESS: f32 = 2.0;
+pub const DEFAULT_WANDER_RADIUS: f32 = 30.0;
+pub const DEFAULT_WANDER_RATE: f32 = 1.2;
+
+/*
+    Chain held by its head at a fixed root, the tip is driven by a
+    FABRIK solve each tick instead of the body following the head.
+
+    With a target in reach the tip heads for it, otherwise it drifts
+    around the tip of the rest pose. Every tick the joints also ease
+    back toward the rest pose so the tentacle keeps its curl.
+*/
+#[derive(Debug, Clone)]
+pub struct Tentacle {
+    pub chain: Chain,
+    pub target: Option<Vec2>,
+    pub tip: Vec2,        // Where the solver is pulling the tip this tick
+    pub reach_speed: f32, // Pixels per second the tip can chase a target
+    pub stiffness: f32,   // How quickly joints relax back to rest, per second
+    pub wander_radius: f32,
+    pub wander_rate: f32, // Radians per second around the wander loop
+    fabrik: Fabrik,
+    rest: Vec<Vec2>,
+    phase: f32,
+    time: f32,
+}
+
+/*
+    Rest pose out from the root, each segment turning curl
+    further than the one before so the tentacle coils.
+
+    heading = rest_heading + curl * index
+    joint[i + 1] = joint[i] + direction( heading ) * spacing
+*/
+fn rest_pose(root: Vec2, heading: f32, curl: f32, spacing: f32, segments: usize) -> Vec<Vec2> {
+    let mut joints = vec![root];
+    for index in 0..segments {
+        let last = joints[index];
+        joints.push(last + Vec2::from_angle(heading + curl * index as f32) * spacing);
+    }
+    joints
+}
+
+impl Tentacle {
+    /*
+        Head sits on the root facing back into the body,
+        so its cap is hidden and the tip end is rounded off.
+    */
+    pub fn new(
+        root: Vec2,
+        heading: f32,
+        curl: f32,
+        spacing: f32,
+        radials: &[f32],
+        bounds: (f32, f32),
+    ) -> Result<Tentacle, ChainError> {
+        let mut chain = Chain::builder()
+            .position(root.x, root.y)
+            .spacing(spacing)
+            .radials(radials.iter().copied())
+            .eyes(Eyes {
+                size: 0.0,
+                ..Eyes::default()
+            })
+            .bounds(bounds.0, bounds.1)
+            .build()?;
+        chain.head.theta = heading + PI;
+        chain.head.speed = 0.0;
+
+        let rest = rest_pose(root, heading, curl, spacing, radials.len());
+        let fabrik = Fabrik::new(rest.clone());
+        fabrik.apply_to(&mut chain.head.children);
+
+        Ok(Tentacle {
+            chain,
+            target: None,
+            tip: rest[rest.len() - 1],
+            reach_speed: DEFAULT_REACH_SPEED,
+            stiffness: DEFAULT_STIFFNESS,
+            wander_radius: DEFAULT_WANDER_RADIUS,
+            wander_rate: DEFAULT_WANDER_RATE,
+            fabrik,
+            rest,
+            phase: 0.0,
+            time: 0.0,
+        })
+    }
+
+    pub fn root(&self) -> Vec2 {
+        self.chain.head.point
+    }
+
+    pub fn reach(&self) -> f32 {
+        self.fabrik.reach()
+    }
+
+    // Offsets this tentacle along the wander loop so a group does not move in step
+    pub fn set_phase(&mut self, phase: f32) {
+        self.phase = phase;
+    }
+
+    /*
+        Wander traces a loop around the rest tip, y runs faster
+        than x so it never settles into a plain circle.
+
+        offset = ( sin( a ), cos( 1.3 * a ) ) * wander_radius
+        a = time * wander_rate + phase
+    */
+    fn goal(&self) -> Vec2 {
+        if let Some(target) = self.target {
+            if target.distance(self.root()) <= self.reach() {
+                return target;
+            }
+        }
+
+        let angle = self.time * self.wander_rate + self.phase;
+        let wander = Vec2::new(angle.sin(), (1.3 * angle).cos()) * self.wander_radius;
+        self.rest[self.rest.len() - 1] + wander
+    }
+
+    /*
+        Relaxing eases each joint toward rest the way an unforced
+        spring settles, whatever dt the ticks come in.
+
+        joint = lerp( joint, rest, 1 - e ^ ( -stiffness * dt ) )
+    */
+    pub fn travel(&mut self, dt: f32) {
+        self.time += dt;
+
+        let offset = self.goal() - self.tip;
+        let step = self.reach_speed * dt;
+        self.tip += if offset.length() > step {
+            offset.normalize() * step
+        } else {
+            offset
+        };
+
+        let relax = 1.0 - (-self.stiffness * dt).exp();
+        for (joint, rest) in self.fabrik.joints_mut().iter_mut().zip(&self.rest).skip(1) {
+            *joint = joint.lerp(*rest, relax);
+        }
+
+        self.chain.reach(&mut self.fabrik, self.tip);
+    }
+
+    pub fn interpolate(&self, next: &Tentacle, t: f32) -> Tentacle {
+        Tentacle {
+            chain: self.chain.interpolate(&next.chain, t),
+            ..next.clone()
+        }
+    }
+}
+
+// Round body that stays where it is, with tentacles spread evenly around it
+#[derive(Debug, Clone)]
+pub struct Octopus {
+    pub centre: Vec2,
+    pub radius: f32,
+    pub eyes: Eyes,
+    pub tentacles: Vec<Tentacle>,
+}
+
+impl Octopus {
+    /*
+        Tentacles root just inside the edge of the body, alternate
+        ones curl opposite ways.
+
+        root = centre + direction( heading ) * radius * 0.8
+        heading = 2π * index / count
+    */
+    pub fn new(
+        centre: Vec2,
+        radius: f32,
+        count: usize,
+        spacing: f32,
+        radials: &[f32],
+        bounds: (f32, f32),
+    ) -> Result<Octopus, ChainError> {
+        let mut tentacles = Vec::with_capacity(count);
+
+        for index in 0..count {
+            let heading = 2.0 * PI * index as f32 / count as f32;
+            let curl = if index % 2 == 0 { 0.08 } else { -0.08 };
+            let root = centre + Vec2::from_angle(heading) * (radius * 0.8);
+
+            let mut tentacle = Tentacle::new(root, heading, curl, spacing, radials, bounds)?;
+            tentacle.set_phase(index as f32 * 2.0 * PI / count as f32);
+            tentacles.push(tentacle);
+        }
+
+        Ok(Octopus {
+            centre,
+            radius,
+            eyes: Eyes::default(),
+            tentacles,
+        })
+    }
+
+    pub fn preset(x: f32, y: f32, width: f32, height: f32) -> Result<Octopus, ChainError> {
+        Octopus::new(
+            Vec2::new(x, y),
+            48.0,
+            8,
+            14.0,
+            &[
+                14.0, 12.0, 11.0, 10.0, 9.0, 8.0, 7.0, 6.0, 5.0, 4.0, 3.0, 2.0,
+            ],
+            (width, height),
+        )
+    }
+
+    // Every tentacle that can reach the target goes for it, the rest wander
+    pub fn reach_for(&mut self, target: Option<Vec2>) {
+        for tentacle in &mut self.tentacles {
+            tentacle.target = target;
+        }
+    }
+
+    pub fn travel(&mut self, dt: f32) {
+        for tentacle in &mut self.tentacles {
+            tentacle.travel(dt);
+        }
+    }
+
+    pub fn interpolate(&self, next: &Octopus, t: f32) -> Octopus {
+        Octopus {
+            tentacles: self
+                .tentacles
+                .iter()
+                .zip(&next.tentacles)
+                .map(|(prev, next)| prev.interpolate(next, t))
+                .collect(),
+            ..next.clone()
+        }
+    }
+
+    // Eyes look up the screen, the body has no heading to follow
+    pub fn eye_points(&self) -> [Vec2; 2] {
+        let reach = self.eyes.offset * self.radius;
+        let up = -PI / 2.0;
+        [
+            self.centre + Vec2::from_angle(up - self.eyes.angle / 2.0) * reach,
+            self.centre + Vec2::from_angle(up + self.eyes.angle / 2.0) * reach,
+        ]
+    }
+
+    pub fn eye_radius(&self) -> f32 {
+        self.eyes.size * self.radius
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tentacle() -> Tentacle {
+        Tentacle::new(
+            Vec2::new(200.0, 200.0),
+            0.0,
+            0.1,
+            10.0,
+            &[5.0; 8],
+            (1000.0, 1000.0),
+        )
+        .unwrap()
+    }
+
+    fn assert_attached(tentacle: &Tentacle, root: Vec2) {
+        assert_eq!(tentacle.root(), root);
+
+        let mut prev = root;
+        for node in &tentacle.chain.head.children {
+            assert!((node.point.distance(prev) - 10.0).abs() < 1e-2);
+            prev = node.point;
+        }
+    }
+
+    #[test]
+    fn tip_reaches_target_in_range() {
+        let mut tentacle = tentacle();
+        let target = Vec2::new(230.0, 250.0);
+        tentacle.target = Some(target);
+
+        for _ in 0..120 {
+            tentacle.travel(1.0 / 60.0);
+        }
+
+        let tip = tentacle.chain.head.children.last().unwrap().point;
+        assert!(tip.distance(target) < 0.5, "{tip}");
+        assert_attached(&tentacle, Vec2::new(200.0, 200.0));
+    }
+
+    #[test]
+    fn wanders_near_rest_when_target_out_of_range() {
+        let mut tentacle = tentacle();
+        tentacle.target = Some(Vec2::new(900.0, 900.0));
+        let rest_tip = tentacle.rest[tentacle.rest.len() - 1];
+
+        let mut tips = vec![];
+        for _ in 0..240 {
+            tentacle.travel(1.0 / 60.0);
+            tips.push(tentacle.chain.head.children.last().unwrap().point);
+        }
+
+        // Never strays far from the rest pose, but does not sit still either
+        for tip in &tips {
+            assert!(tip.distance(rest_tip) < tentacle.wander_radius * 2.0);
+        }
+        assert!(tips[60].distance(tips[200]) > 1.0);
+        assert_attached(&tentacle, Vec2::new(200.0, 200.0));
+    }
+
+    #[test]
+    fn relaxes_back_to_rest() {
+        let mut tentacle = tentacle();
+        tentacle.wander_radius = 0.0;
+        tentacle.target = Some(Vec2::new(200.0, 260.0));
+        for _ in 0..120 {
+            tentacle.travel(1.0 / 60.0);
+        }
+
+        tentacle.target = None;
+        for _ in 0..600 {
+            tentacle.travel(1.0 / 60.0);
+        }
+
+        for (node, rest) in tentacle.chain.head.children.iter().zip(&tentacle.rest[1..]) {
+            assert!(node.point.distance(*rest) < 1.0);
+        }
+    }
+
+    #[test]
+    fn octopus_spreads_tentacles() {
+        let mut octopus = Octopus::preset(500.0, 400.0, 1000.0, 800.0).unwrap();
+        assert_eq!(octopus.tentacles.len(), 8);
+
+        let roots: Vec<Vec2> = octopus.tentacles.iter().map(|t| t.root()).collect();
+        for root in &roots {
+            let distance = root.distance(octopus.centre);
+            assert_eq!(
+                format!("{distance:.3}"),
+                format!("{:.3}", octopus.radius * 0.8)
+            );
+        }
+
+        // Roots never move, whatever the tentacles are doing
+        octopus.reach_for(Some(Vec2::new(600.0, 400.0)));
+        for _ in 0..60 {
+            octopus.travel(1.0 / 60.0);
+        }
+        for (tentacle, root) in octopus.tentacles.iter().zip(roots) {
+            assert_eq!(tentacle.root(), root);
+        }
+    }
+}
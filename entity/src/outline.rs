@@ -0,0 +1,311 @@
+use std::f32::consts::PI;
+
+use crate::{
+    chain::{Chain, Head, Node},
+    spline::catmull_rom_closed,
+    vector::Vec2,
+};
+
+// Points along each rounded cap, not counting the side points it joins
+pub const CAP_SEGMENTS: usize = 8;
+
+/*
+    Points on a circle from start to end, both included.
+    Angles step evenly so end < start sweeps the other way.
+*/
+pub(crate) fn arc(center: &Vec2, radius: f32, start: f32, end: f32, segments: usize) -> Vec<Vec2> {
+    let segments = segments.max(1);
+
+    (0..=segments)
+        .map(|step| {
+            let theta = start + (end - start) * step as f32 / segments as f32;
+            *center + Vec2::from_angle(theta) * radius
+        })
+        .collect()
+}
+
+/*
+    Closed loop around the body, the last point joins back to the first.
+
+    head cap: right side of the head, round the front, to the left side
+    left sides: each node from head to tail
+    tail cap: left side of the last node, round the back, to its right side
+    right sides: each node from tail back to head
+
+    A head with no nodes behind it gives a full circle.
+*/
+pub fn body_outline(head: &Head, cap_segments: usize) -> Vec<Vec2> {
+    nodes_outline(
+        &head.point,
+        head.radial,
+        head.theta,
+        &head.children,
+        cap_segments,
+    )
+}
+
+// Same loop as body_outline for nodes trailing any leading point, not just a head
+pub fn nodes_outline(
+    point: &Vec2,
+    radial: f32,
+    theta: f32,
+    nodes: &[Node],
+    cap_segments: usize,
+) -> Vec<Vec2> {
+    let mut outline = arc(
+        point,
+        radial,
+        theta + PI / 2.0,
+        theta - PI / 2.0,
+        cap_segments,
+    );
+
+    outline.extend(nodes.iter().map(|node| node.sides.left));
+
+    let (tail_point, tail_radial, tail_theta) = match nodes.last() {
+        Some(tail) => (tail.point, tail.radial, tail.theta),
+        None => (*point, radial, theta),
+    };
+
+    outline.extend(arc(
+        &tail_point,
+        tail_radial,
+        tail_theta - PI / 2.0,
+        tail_theta - 3.0 * PI / 2.0,
+        cap_segments,
+    ));
+
+    outline.extend(nodes.iter().rev().map(|node| node.sides.right));
+
+    outline
+}
+
+// Shoelace formula, positive when the cross products of each edge sum positive
+fn signed_area(polygon: &[Vec2]) -> f32 {
+    let mut area = 0.0;
+    for (index, point) in polygon.iter().enumerate() {
+        area += point.cross(polygon[(index + 1) % polygon.len()]);
+    }
+    area / 2.0
+}
+
+// Edges count as inside, assumes a, b, c wind positively
+fn in_triangle(point: Vec2, a: Vec2, b: Vec2, c: Vec2) -> bool {
+    (b - a).cross(point - a) >= 0.0
+        && (c - b).cross(point - b) >= 0.0
+        && (a - c).cross(point - c) >= 0.0
+}
+
+/*
+    Ear clipping, fills any simple polygon including concave ones
+    such as a smoothed outline.
+
+    A corner is an ear when it turns the same way as the polygon winds
+    and no other point sits inside the triangle it makes with its
+    neighbours. Each ear is cut off until one triangle is left.
+
+    Outlines that cross themselves can run out of ears, whatever is
+    left is then fanned so the fill still covers it.
+*/
+pub fn triangulate(polygon: &[Vec2]) -> Vec<[Vec2; 3]> {
+    let mut remaining = polygon.to_vec();
+    if remaining.len() < 3 {
+        return vec![];
+    }
+
+    if signed_area(&remaining) < 0.0 {
+        remaining.reverse();
+    }
+
+    let mut triangles = Vec::with_capacity(remaining.len() - 2);
+    let mut index = 0;
+    let mut since_last_ear = 0;
+
+    while remaining.len() > 3 {
+        let count = remaining.len();
+        let prev_index = (index + count - 1) % count;
+        let next_index = (index + 1) % count;
+
+        let (prev, curr, next) = (
+            remaining[prev_index],
+            remaining[index],
+            remaining[next_index],
+        );
+
+        let is_ear = (curr - prev).cross(next - curr) > 0.0
+            && !remaining.iter().enumerate().any(|(other, point)| {
+                other != prev_index
+                    && other != index
+                    && other != next_index
+                    && in_triangle(*point, prev, curr, next)
+            });
+
+        if is_ear {
+            triangles.push([prev, curr, next]);
+            remaining.remove(index);
+            index %= remaining.len();
+            since_last_ear = 0;
+            continue;
+        }
+
+        since_last_ear += 1;
+        if since_last_ear > count {
+            for pair in remaining[1..].windows(2) {
+                triangles.push([remaining[0], pair[0], pair[1]]);
+            }
+            return triangles;
+        }
+
+        index = next_index;
+    }
+
+    triangles.push([remaining[0], remaining[1], remaining[2]]);
+    triangles
+}
+
+impl Chain {
+    pub fn outline(&self) -> Vec<Vec2> {
+        body_outline(&self.head, CAP_SEGMENTS)
+    }
+
+    // Outline rounded off by a spline, sampled samples_per_segment times between points
+    pub fn smooth_outline(&self, samples_per_segment: usize) -> Vec<Vec2> {
+        catmull_rom_closed(&self.outline(), samples_per_segment)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::Sides;
+
+    fn polygon_area(points: &[Vec2]) -> f32 {
+        signed_area(points).abs()
+    }
+
+    fn triangles_area(triangles: &[[Vec2; 3]]) -> f32 {
+        triangles
+            .iter()
+            .map(|[a, b, c]| (*b - *a).cross(*c - *a).abs() / 2.0)
+            .sum()
+    }
+
+    fn straight_chain() -> Chain {
+        let mut chain = Chain::builder()
+            .position(500.0, 500.0)
+            .spacing(10.0)
+            .radials([8.0, 6.0, 4.0])
+            .head_radial(8.0)
+            .build()
+            .unwrap();
+        chain.head.theta = PI / 2.0;
+        chain
+    }
+
+    #[test]
+    fn outline_order() {
+        let chain = straight_chain();
+        let outline = chain.outline();
+        let nodes = &chain.head.children;
+
+        // Two caps plus a left and right point per node
+        assert_eq!(outline.len(), (CAP_SEGMENTS + 1) * 2 + nodes.len() * 2);
+
+        let head_sides = Sides::from((&chain.head.point, &chain.head.radial, &chain.head.theta));
+        assert!(outline[0].distance(head_sides.right) < 1e-4);
+        assert!(outline[CAP_SEGMENTS].distance(head_sides.left) < 1e-4);
+
+        let lefts = &outline[CAP_SEGMENTS + 1..CAP_SEGMENTS + 1 + nodes.len()];
+        for (point, node) in lefts.iter().zip(nodes) {
+            assert_eq!(*point, node.sides.left);
+        }
+
+        let tail_start = CAP_SEGMENTS + 1 + nodes.len();
+        let tail = nodes.last().unwrap();
+        assert!(outline[tail_start].distance(tail.sides.left) < 1e-4);
+        assert!(outline[tail_start + CAP_SEGMENTS].distance(tail.sides.right) < 1e-4);
+
+        let rights = &outline[tail_start + CAP_SEGMENTS + 1..];
+        for (point, node) in rights.iter().zip(nodes.iter().rev()) {
+            assert_eq!(*point, node.sides.right);
+        }
+    }
+
+    #[test]
+    fn caps_stay_on_their_circles() {
+        let chain = straight_chain();
+        let outline = chain.outline();
+
+        for point in &outline[..=CAP_SEGMENTS] {
+            let distance = point.distance(chain.head.point);
+            assert!((distance - chain.head.radial).abs() < 1e-3);
+
+            // Head cap is out in front of the head
+            let ahead = Vec2::from_angle(chain.head.theta).dot(*point - chain.head.point);
+            assert!(ahead >= -1e-3);
+        }
+    }
+
+    #[test]
+    fn empty_chain_is_a_circle() {
+        let chain = Chain::builder()
+            .position(50.0, 50.0)
+            .head_radial(10.0)
+            .build()
+            .unwrap();
+
+        let outline = chain.outline();
+        assert_eq!(outline.len(), (CAP_SEGMENTS + 1) * 2);
+        for point in &outline {
+            assert!((point.distance(chain.head.point) - 10.0).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn triangulate_concave() {
+        // L shape, the corner at (5, 5) is reflex
+        let l_shape = vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(10.0, 0.0),
+            Vec2::new(10.0, 5.0),
+            Vec2::new(5.0, 5.0),
+            Vec2::new(5.0, 10.0),
+            Vec2::new(0.0, 10.0),
+        ];
+
+        for polygon in [l_shape.clone(), l_shape.into_iter().rev().collect()] {
+            let triangles = triangulate(&polygon);
+            assert_eq!(triangles.len(), 4);
+            assert_eq!(triangles_area(&triangles), 75.0);
+
+            // Nothing should be filled in the missing corner
+            let outside = Vec2::new(7.5, 7.5);
+            for [a, b, c] in &triangles {
+                let inside = in_triangle(outside, *a, *b, *c) || in_triangle(outside, *a, *c, *b);
+                assert!(!inside);
+            }
+        }
+    }
+
+    #[test]
+    fn smooth_outline_fills_like_outline() {
+        let mut chain = straight_chain();
+        chain.head.theta = 0.0;
+        chain.seek(Vec2::new(700.0, 800.0));
+        for _ in 0..30 {
+            chain.travel(1.0 / 30.0);
+        }
+
+        let raw = chain.outline();
+        let smooth = chain.smooth_outline(4);
+        assert!(smooth.len() > raw.len());
+
+        // Rounding the corners changes the area only a little
+        let raw_area = polygon_area(&raw);
+        let smooth_area = polygon_area(&smooth);
+        assert!((raw_area - smooth_area).abs() / raw_area < 0.1);
+
+        let filled = triangles_area(&triangulate(&smooth));
+        assert!((filled - smooth_area).abs() / smooth_area < 1e-3);
+    }
+}
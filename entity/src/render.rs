@@ -0,0 +1,280 @@
+use std::f32::consts::TAU;
+
+use crate::{
+    chain::{Chain, Node},
+    creature::Creature,
+    fish::FinKind,
+    outline::arc,
+    skeleton::Branch,
+    spline::catmull_rom_closed,
+    vector::Vec2,
+};
+
+// Spline samples between each pair of outline points
+pub const OUTLINE_SAMPLES: usize = 4;
+
+pub type Colour = [f32; 4];
+
+pub const BODY: Colour = [0.35, 0.55, 0.85, 1.0];
+pub const FIN: Colour = [0.55, 0.75, 0.95, 1.0];
+pub const OUTLINE: Colour = [1.0, 1.0, 1.0, 1.0];
+pub const EYE: Colour = [0.95, 0.95, 0.95, 1.0];
+pub const NODE: Colour = [0.95, 0.6, 0.3, 1.0];
+
+const OUTLINE_WIDTH: f32 = 2.0;
+const LEG_WIDTH: f32 = 12.0;
+const BONE_WIDTH: f32 = 1.5;
+const JOINT_RADIUS: f32 = 2.5;
+
+// Longest straight piece an arc is broken into
+const ARC_STEP: f32 = 4.0;
+
+// Most pieces one arc is broken into, however large or long it is
+const MAX_ARC_PIECES: usize = 4096;
+
+/*
+    Everything a creature needs to draw itself, in screen space with
+    y down and angles measured like headings, 0 pointing right.
+    Colours are RGBA fractions.
+
+    Backends live with whatever they draw to, the draw functions
+    below decide what goes on top of what for all of them.
+*/
+pub trait Renderer {
+    fn fill_polygon(&mut self, colour: Colour, points: &[Vec2]);
+
+    // Joins the points with lines width across, back to the first when closed
+    fn stroke_path(&mut self, colour: Colour, width: f32, points: &[Vec2], closed: bool);
+
+    fn circle(&mut self, colour: Colour, centre: Vec2, radius: f32);
+
+    // Outline of the circle running from start to end
+    fn arc(&mut self, colour: Colour, width: f32, centre: Vec2, radius: f32, start: f32, end: f32);
+
+    // Position is the left end of the baseline, backends without a font skip it
+    fn text(&mut self, colour: Colour, size: u32, position: Vec2, text: &str);
+}
+
+/*
+    Points along an arc for backends that draw it as straight pieces,
+    each no longer than ARC_STEP, so every backend breaks the same arc
+    the same way.
+
+    pieces = ceil( |end - start| * radius / ARC_STEP )
+*/
+pub fn arc_points(centre: Vec2, radius: f32, start: f32, end: f32) -> Vec<Vec2> {
+    let pieces = ((end - start).abs() * radius / ARC_STEP).ceil() as usize;
+    arc(&centre, radius, start, end, pieces.clamp(1, MAX_ARC_PIECES))
+}
+
+// Fills a closed shape then traces round its edge
+pub fn draw_shape(r: &mut impl Renderer, colour: Colour, points: &[Vec2]) {
+    r.fill_polygon(colour, points);
+    r.stroke_path(OUTLINE, OUTLINE_WIDTH, points, true);
+}
+
+pub fn draw_eyes(r: &mut impl Renderer, eyes: [Vec2; 2], radius: f32) {
+    if radius <= 0.0 {
+        return;
+    }
+    for eye in eyes {
+        r.circle(EYE, eye, radius);
+    }
+}
+
+pub fn draw_chain(r: &mut impl Renderer, chain: &Chain) {
+    draw_shape(r, BODY, &chain.smooth_outline(OUTLINE_SAMPLES));
+
+    let sides = chain.head.sides();
+    draw_eyes(
+        r,
+        [sides.left_eye, sides.right_eye],
+        chain.head.eye_radius(),
+    );
+}
+
+// Each node's sides joined across the body, the node itself marked on top
+pub fn draw_nodes(r: &mut impl Renderer, chain: &Chain) {
+    for node in &chain.head.children {
+        r.stroke_path(OUTLINE, 1.0, &[node.sides.left, node.sides.right], false);
+        r.circle(NODE, node.point, 2.5);
+    }
+}
+
+pub fn draw_creature(r: &mut impl Renderer, creature: &Creature) {
+    match creature {
+        Creature::Chain(chain) => draw_chain(r, chain),
+        // Paired fins and the tail sit under the body, the dorsal fin on top
+        Creature::Fish(fish) => {
+            let fins = fish.fin_polygons();
+            draw_shape(r, FIN, &fish.tail_polygon());
+            for (kind, points) in &fins {
+                if *kind != FinKind::Dorsal {
+                    draw_shape(r, FIN, points);
+                }
+            }
+
+            draw_chain(r, &fish.chain);
+
+            for (kind, points) in &fins {
+                if *kind == FinKind::Dorsal {
+                    draw_shape(r, FIN, points);
+                }
+            }
+        }
+        Creature::Lizard(lizard) => {
+            for leg in &lizard.legs {
+                let Some(hip) = leg.hip(&lizard.chain.head) else {
+                    continue;
+                };
+                r.stroke_path(BODY, LEG_WIDTH, &[hip, leg.knee, leg.foot], false);
+            }
+
+            draw_chain(r, &lizard.chain);
+        }
+        Creature::Octopus(octopus) => {
+            for tentacle in &octopus.tentacles {
+                let outline = tentacle.chain.smooth_outline(OUTLINE_SAMPLES);
+                draw_shape(r, BODY, &outline);
+            }
+
+            r.circle(BODY, octopus.centre, octopus.radius);
+            r.arc(
+                OUTLINE,
+                OUTLINE_WIDTH,
+                octopus.centre,
+                octopus.radius,
+                0.0,
+                TAU,
+            );
+
+            draw_eyes(r, octopus.eye_points(), octopus.eye_radius());
+        }
+        Creature::Skeleton(skeleton) => {
+            for outline in skeleton.branch_outlines() {
+                draw_shape(r, FIN, &catmull_rom_closed(&outline, OUTLINE_SAMPLES));
+            }
+
+            draw_chain(r, &skeleton.spine);
+        }
+    }
+}
+
+// A line through the points with each joint marked on top
+pub fn draw_bones(r: &mut impl Renderer, points: &[Vec2]) {
+    r.stroke_path(NODE, BONE_WIDTH, points, false);
+    for point in points {
+        r.circle(NODE, *point, JOINT_RADIUS);
+    }
+}
+
+// Head then every node, the line the body is built around
+pub fn draw_spine(r: &mut impl Renderer, chain: &Chain) {
+    let mut points = vec![chain.head.point];
+    points.extend(chain.head.children.iter().map(|node| node.point));
+    draw_bones(r, &points);
+}
+
+fn draw_branch_bones(r: &mut impl Renderer, branches: &[Branch], parents: &[Node]) {
+    for branch in branches {
+        let Some(parent) = parents.get(branch.node) else {
+            continue;
+        };
+
+        let mut points = vec![parent.point];
+        points.extend(branch.nodes.iter().map(|node| node.point));
+        draw_bones(r, &points);
+
+        draw_branch_bones(r, &branch.branches, &branch.nodes);
+    }
+}
+
+// Bones of everything draw_creature covers, meant to go on top of it
+pub fn draw_skeleton(r: &mut impl Renderer, creature: &Creature) {
+    match creature {
+        Creature::Chain(chain) => draw_spine(r, chain),
+        Creature::Fish(fish) => draw_spine(r, &fish.chain),
+        Creature::Lizard(lizard) => {
+            for leg in &lizard.legs {
+                if let Some(hip) = leg.hip(&lizard.chain.head) {
+                    draw_bones(r, &[hip, leg.knee, leg.foot]);
+                }
+            }
+
+            draw_spine(r, &lizard.chain);
+        }
+        Creature::Octopus(octopus) => {
+            for tentacle in &octopus.tentacles {
+                draw_spine(r, &tentacle.chain);
+            }
+        }
+        Creature::Skeleton(skeleton) => {
+            draw_branch_bones(r, &skeleton.branches, &skeleton.spine.head.children);
+            draw_spine(r, &skeleton.spine);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+
+    use super::*;
+    use crate::fish::Fish;
+
+    // Keeps the colour of every filled shape in the order drawn
+    #[derive(Default)]
+    struct Fills(Vec<Colour>);
+
+    impl Renderer for Fills {
+        fn fill_polygon(&mut self, colour: Colour, _points: &[Vec2]) {
+            self.0.push(colour);
+        }
+
+        fn stroke_path(&mut self, _colour: Colour, _width: f32, _points: &[Vec2], _closed: bool) {}
+
+        fn circle(&mut self, _colour: Colour, _centre: Vec2, _radius: f32) {}
+
+        fn arc(&mut self, _: Colour, _: f32, _: Vec2, _: f32, _: f32, _: f32) {}
+
+        fn text(&mut self, _colour: Colour, _size: u32, _position: Vec2, _text: &str) {}
+    }
+
+    #[test]
+    fn fish_body_sits_between_its_fins() {
+        let fish = Fish::preset(300.0, 300.0, 1000.0, 1000.0).unwrap();
+        let mut fills = Fills::default();
+        draw_creature(&mut fills, &Creature::Fish(fish.clone()));
+
+        // Tail and paired fins, the body, then the dorsal fin
+        let under = fish
+            .fin_polygons()
+            .iter()
+            .filter(|(kind, _)| *kind != FinKind::Dorsal)
+            .count()
+            + 1;
+        assert_eq!(fills.0.len(), fish.fin_polygons().len() + 2);
+        assert!(fills.0[..under].iter().all(|colour| *colour == FIN));
+        assert_eq!(fills.0[under], BODY);
+        assert_eq!(fills.0[under + 1..], [FIN]);
+    }
+
+    #[test]
+    fn arc_points_step_evenly_up_to_a_cap() {
+        let centre = Vec2::new(10.0, 10.0);
+        let points = arc_points(centre, 8.0, 0.0, PI);
+        assert_eq!(points.len(), 8);
+        for pair in points.windows(2) {
+            assert!(pair[0].distance(pair[1]) <= ARC_STEP);
+        }
+        assert!(points[0].distance(Vec2::new(18.0, 10.0)) < 1e-4);
+        assert!(points[7].distance(Vec2::new(2.0, 10.0)) < 1e-4);
+
+        assert_eq!(arc_points(centre, 1e9, 0.0, TAU).len(), MAX_ARC_PIECES + 1);
+        assert_eq!(
+            arc_points(centre, 5.0, 0.0, f32::INFINITY).len(),
+            MAX_ARC_PIECES + 1
+        );
+        assert_eq!(arc_points(centre, 5.0, 1.0, 1.0).len(), 2);
+    }
+}
@@ -0,0 +1,390 @@
+use std::f32::consts::PI;
+
+use crate::{
+    chain::{Chain, Node, Sides, DEFAULT_MAX_BEND},
+    error::ChainError,
+    helper_functions::{lerp_angle, pull_node_on_point},
+    limb::Side,
+    outline::{nodes_outline, CAP_SEGMENTS},
+    vector::Vec2,
+};
+
+/*
+    Side chain hanging off a node of whatever it is attached to,
+    the spine or another branch, with its own spacing and radii.
+
+    Angle is measured from straight back along the parent, so 0 trails
+    behind like a tail, π / 2 sticks out to the left and -π / 2 to the right.
+    Each branch can carry branches of its own, making a tree.
+*/
+#[derive(Debug, Clone)]
+pub struct Branch {
+    pub node: usize,        // Index into the parent's nodes this hangs from
+    pub side: Option<Side>, // Which side of the parent node it roots on, centre if None
+    pub angle: f32,
+    pub spacing: f32,
+    pub nodes: Vec<Node>,
+    pub branches: Vec<Branch>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Skeleton {
+    pub spine: Chain,
+    pub branches: Vec<Branch>, // Hang off the spine's nodes
+}
+
+// Where a branch roots and the heading its first node bends from
+fn anchor(parent: &Node, side: Option<Side>, angle: f32) -> (Vec2, f32) {
+    let point = match side {
+        Some(Side::Left) => parent.sides.left,
+        Some(Side::Right) => parent.sides.right,
+        None => parent.point,
+    };
+    (point, parent.theta + angle)
+}
+
+impl Branch {
+    pub fn new(
+        node: usize,
+        angle: f32,
+        spacing: f32,
+        radials: impl IntoIterator<Item = f32>,
+    ) -> Result<Branch, ChainError> {
+        if !spacing.is_finite() || spacing <= 0.0 {
+            return Err(ChainError::InvalidSpacing(spacing));
+        }
+
+        let mut nodes = vec![];
+        for (index, radial) in radials.into_iter().enumerate() {
+            if !radial.is_finite() || radial < 0.0 {
+                return Err(ChainError::InvalidRadius {
+                    index,
+                    radius: radial,
+                });
+            }
+
+            nodes.push(Node {
+                point: Vec2::ZERO,
+                radial,
+                theta: 0.0,
+                max_bend: DEFAULT_MAX_BEND,
+                sides: Sides::default(),
+            });
+        }
+
+        Ok(Branch {
+            node,
+            side: None,
+            angle,
+            spacing,
+            nodes,
+            branches: vec![],
+        })
+    }
+
+    pub fn on_side(mut self, side: Side) -> Branch {
+        self.side = Some(side);
+        self
+    }
+
+    pub fn with_max_bend(mut self, max_bend: f32) -> Branch {
+        for node in &mut self.nodes {
+            node.max_bend = max_bend;
+        }
+        self
+    }
+
+    pub fn with_branch(mut self, branch: Branch) -> Branch {
+        self.branches.push(branch);
+        self
+    }
+
+    /*
+        Lays the nodes out straight from the anchor at their rest angle.
+        Node headings face back toward the anchor.
+
+        out = anchor_heading + π
+        node[i] = anchor + direction( out ) * spacing * ( i + 1 )
+    */
+    fn settle(&mut self, parent: &Node) {
+        let (root, heading) = anchor(parent, self.side, self.angle);
+        let out = Vec2::from_angle(heading + PI);
+
+        for (index, node) in self.nodes.iter_mut().enumerate() {
+            node.point = root + out * (self.spacing * (index + 1) as f32);
+            node.theta = heading;
+            node.update_sides();
+        }
+
+        settle_all(&mut self.branches, &self.nodes);
+    }
+
+    // Follow the leader from the anchor, then everything hanging off this branch
+    fn solve(&mut self, parent: &Node) {
+        let (mut prev, mut heading) = anchor(parent, self.side, self.angle);
+
+        for node in &mut self.nodes {
+            pull_node_on_point(&prev, &heading, node, &self.spacing);
+            prev = node.point;
+            heading = node.theta;
+        }
+
+        solve_all(&mut self.branches, &self.nodes);
+    }
+
+    fn shift(&mut self, offset: Vec2) {
+        for node in &mut self.nodes {
+            node.point += offset;
+            node.update_sides();
+        }
+        for branch in &mut self.branches {
+            branch.shift(offset);
+        }
+    }
+
+    fn interpolate(&self, next: &Branch, t: f32) -> Branch {
+        let mut blended = next.clone();
+
+        for (node, prev) in blended.nodes.iter_mut().zip(&self.nodes) {
+            node.point = prev.point.lerp(node.point, t);
+            node.theta = lerp_angle(prev.theta, node.theta, t);
+            node.update_sides();
+        }
+
+        blended.branches = self
+            .branches
+            .iter()
+            .zip(&next.branches)
+            .map(|(prev, next)| prev.interpolate(next, t))
+            .collect();
+
+        blended
+    }
+
+    // Closed outline of this branch alone, rounded off at the root
+    pub fn outline(&self, parent: &Node) -> Vec<Vec2> {
+        let (root, heading) = anchor(parent, self.side, self.angle);
+        let radial = self.nodes.first().map_or(0.0, |node| node.radial);
+        nodes_outline(&root, radial, heading, &self.nodes, CAP_SEGMENTS)
+    }
+}
+
+// Branches hanging off nodes that do not exist are left where they are
+fn settle_all(branches: &mut [Branch], parents: &[Node]) {
+    for branch in branches {
+        if let Some(parent) = parents.get(branch.node) {
+            branch.settle(parent);
+        }
+    }
+}
+
+fn solve_all(branches: &mut [Branch], parents: &[Node]) {
+    for branch in branches {
+        if let Some(parent) = parents.get(branch.node) {
+            branch.solve(parent);
+        }
+    }
+}
+
+fn outlines_of(branches: &[Branch], parents: &[Node], outlines: &mut Vec<Vec<Vec2>>) {
+    for branch in branches {
+        if let Some(parent) = parents.get(branch.node) {
+            outlines.push(branch.outline(parent));
+            outlines_of(&branch.branches, &branch.nodes, outlines);
+        }
+    }
+}
+
+impl Skeleton {
+    pub fn new(spine: Chain, branches: Vec<Branch>) -> Skeleton {
+        let mut skeleton = Skeleton { spine, branches };
+        settle_all(&mut skeleton.branches, &skeleton.spine.head.children);
+        skeleton
+    }
+
+    /*
+        Spine moves first, then every branch is pulled after the node
+        it hangs from in the same tick, parents before their children.
+
+        A spine node carried round the arena by wrapping takes
+        its branches with it rather than dragging them across.
+    */
+    pub fn travel(&mut self, dt: f32) {
+        let before: Vec<Vec2> = self.spine.head.children.iter().map(|n| n.point).collect();
+        self.spine.travel(dt);
+
+        let arena = self.spine.arena();
+        let limit = arena.width().min(arena.height()) / 2.0;
+        let nodes = &self.spine.head.children;
+
+        for branch in &mut self.branches {
+            if let (Some(node), Some(before)) = (nodes.get(branch.node), before.get(branch.node)) {
+                let moved = node.point - *before;
+                if moved.length() > limit {
+                    branch.shift(moved);
+                }
+            }
+        }
+
+        solve_all(&mut self.branches, &self.spine.head.children);
+    }
+
+    pub fn interpolate(&self, next: &Skeleton, t: f32) -> Skeleton {
+        let spine = self.spine.interpolate(&next.spine, t);
+
+        // Wrapping round the arena jumps too far to blend, as with the spine alone
+        let arena = next.spine.arena();
+        let jump = self.spine.head.point.distance(next.spine.head.point);
+        if jump > arena.width().min(arena.height()) / 2.0 {
+            return next.clone();
+        }
+
+        Skeleton {
+            spine,
+            branches: self
+                .branches
+                .iter()
+                .zip(&next.branches)
+                .map(|(prev, next)| prev.interpolate(next, t))
+                .collect(),
+        }
+    }
+
+    // Outline of every branch in the tree, parents before children
+    pub fn branch_outlines(&self) -> Vec<Vec<Vec2>> {
+        let mut outlines = vec![];
+        outlines_of(&self.branches, &self.spine.head.children, &mut outlines);
+        outlines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helper_functions::wrap_angle;
+
+    fn spine() -> Chain {
+        let mut chain = Chain::builder()
+            .position(500.0, 500.0)
+            .spacing(20.0)
+            .radials([10.0; 6])
+            .build()
+            .unwrap();
+        chain.head.theta = 0.0;
+        chain.head.desired_heading = Some(0.0);
+        for _ in 0..30 {
+            chain.travel(1.0 / 30.0);
+        }
+        chain
+    }
+
+    fn assert_spacing(branch: &Branch, parent: &Node) {
+        let (mut prev, _) = anchor(parent, branch.side, branch.angle);
+        for node in &branch.nodes {
+            assert!(node.point.distance(prev) <= branch.spacing + 1e-3);
+            prev = node.point;
+        }
+    }
+
+    #[test]
+    fn branches_settle_at_their_angle() {
+        let skeleton = Skeleton::new(
+            spine(),
+            vec![Branch::new(1, PI / 2.0, 8.0, [4.0, 3.0, 2.0])
+                .unwrap()
+                .on_side(Side::Left)],
+        );
+
+        // Heading right, so out to the left is up the screen
+        let parent = &skeleton.spine.head.children[1];
+        let branch = &skeleton.branches[0];
+        let expected = parent.sides.left + Vec2::from_angle(parent.theta - PI / 2.0) * 8.0;
+        assert!(branch.nodes[0].point.distance(expected) < 1e-3);
+        assert!(branch.nodes[2].point.y < branch.nodes[1].point.y);
+        assert_spacing(branch, parent);
+    }
+
+    #[test]
+    fn branches_follow_the_spine() {
+        let mut skeleton = Skeleton::new(
+            spine(),
+            vec![
+                Branch::new(2, PI / 3.0, 10.0, [5.0; 4]).unwrap(),
+                Branch::new(4, 0.0, 12.0, [4.0; 3])
+                    .unwrap()
+                    .with_branch(Branch::new(1, -PI / 4.0, 6.0, [2.0; 3]).unwrap()),
+            ],
+        );
+
+        skeleton.spine.seek(Vec2::new(800.0, 900.0));
+        for _ in 0..90 {
+            skeleton.travel(1.0 / 30.0);
+
+            let nodes = &skeleton.spine.head.children;
+            for branch in &skeleton.branches {
+                assert_spacing(branch, &nodes[branch.node]);
+                for child in &branch.branches {
+                    assert_spacing(child, &branch.nodes[child.node]);
+                }
+            }
+        }
+
+        // First joint of each branch stays within its bend of the rest angle
+        let nodes = &skeleton.spine.head.children;
+        for branch in &skeleton.branches {
+            let (_, heading) = anchor(&nodes[branch.node], branch.side, branch.angle);
+            let bend = wrap_angle(branch.nodes[0].theta - heading).abs();
+            assert!(bend <= DEFAULT_MAX_BEND + 1e-3);
+        }
+    }
+
+    #[test]
+    fn branches_travel_across_a_wrap() {
+        let mut chain = spine();
+        chain.set_boundary_mode(crate::arena::BoundaryMode::Wrap);
+        chain.head.target = None;
+        let mut skeleton = Skeleton::new(chain, vec![Branch::new(0, 0.0, 10.0, [3.0; 3]).unwrap()]);
+
+        for _ in 0..120 {
+            skeleton.travel(1.0 / 30.0);
+            let parent = &skeleton.spine.head.children[0];
+            assert_spacing(&skeleton.branches[0], parent);
+        }
+    }
+
+    #[test]
+    fn outlines_cover_the_tree() {
+        let skeleton = Skeleton::new(
+            spine(),
+            vec![Branch::new(1, PI / 2.0, 8.0, [4.0, 3.0])
+                .unwrap()
+                .with_branch(Branch::new(0, 0.0, 4.0, [2.0]).unwrap())
+                .with_branch(Branch::new(9, 0.0, 4.0, [2.0]).unwrap())],
+        );
+
+        // The branch hanging off a missing node is skipped
+        assert_eq!(skeleton.branch_outlines().len(), 2);
+    }
+
+    #[test]
+    fn rejects_bad_branches() {
+        assert_eq!(
+            Branch::new(0, 0.0, 0.0, [1.0]).unwrap_err(),
+            ChainError::InvalidSpacing(0.0)
+        );
+        assert_eq!(
+            Branch::new(0, 0.0, 4.0, [1.0, -2.0]).unwrap_err(),
+            ChainError::InvalidRadius {
+                index: 1,
+                radius: -2.0
+            }
+        );
+        assert_eq!(
+            Branch::new(0, 0.0, 4.0, [f32::INFINITY]).unwrap_err(),
+            ChainError::InvalidRadius {
+                index: 0,
+                radius: f32::INFINITY
+            }
+        );
+    }
+}
@@ -0,0 +1,338 @@
+use std::{collections::HashMap, hash::Hash};
+
+use crate::{vector::Vec2, world::CreatureId};
+
+type Cell = (i32, i32);
+
+/*
+    Uniform grid over the plane, each point goes in the square cell
+    of cell_size it falls in.
+
+    cell = ( floor( x / cell_size ), floor( y / cell_size ) )
+
+    A radius query only has to look through the cells the circle
+    overlaps, so with cell_size near the query radius that is the
+    surrounding 3 x 3 block instead of every point.
+
+    Points are stored under a key, inserting a key again moves it,
+    which only touches the cells involved when it has changed cell.
+    Only occupied cells are kept, a cell goes when its last point leaves.
+*/
+#[derive(Debug, Clone)]
+pub struct SpatialGrid<K = usize> {
+    cell_size: f32,
+    cells: HashMap<Cell, Vec<(K, Vec2)>>, // Never holds an empty cell
+    entries: HashMap<K, (Cell, usize)>,   // Cell each key is filed under and where in it
+}
+
+// A head or body node of one creature in a World
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeKey {
+    pub creature: CreatureId,
+    pub node: Option<usize>, // Index into the chain's nodes, None for the head
+}
+
+impl SpatialGrid<usize> {
+    // Grid holding each point under its index in the slice
+    pub fn from_points(cell_size: f32, points: &[Vec2]) -> SpatialGrid<usize> {
+        let mut grid = SpatialGrid::new(cell_size);
+        for (index, point) in points.iter().enumerate() {
+            grid.insert(index, *point);
+        }
+        grid
+    }
+}
+
+impl<K: Copy + Eq + Hash> SpatialGrid<K> {
+    pub fn new(cell_size: f32) -> SpatialGrid<K> {
+        SpatialGrid {
+            cell_size: cell_size.max(f32::EPSILON),
+            cells: HashMap::new(),
+            entries: HashMap::new(),
+        }
+    }
+
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn contains(&self, key: K) -> bool {
+        self.entries.contains_key(&key)
+    }
+
+    fn cell_of(&self, point: Vec2) -> Cell {
+        (
+            (point.x / self.cell_size).floor() as i32,
+            (point.y / self.cell_size).floor() as i32,
+        )
+    }
+
+    // Adds the key or moves it if already there
+    pub fn insert(&mut self, key: K, point: Vec2) {
+        let cell = self.cell_of(point);
+
+        if let Some(&(old, slot)) = self.entries.get(&key) {
+            if old == cell {
+                self.cells.get_mut(&cell).expect("filed cell exists")[slot].1 = point;
+                return;
+            }
+            self.take(old, slot);
+        }
+
+        let entries = self.cells.entry(cell).or_default();
+        entries.push((key, point));
+        self.entries.insert(key, (cell, entries.len() - 1));
+    }
+
+    pub fn remove(&mut self, key: K) -> bool {
+        match self.entries.remove(&key) {
+            Some((cell, slot)) => {
+                self.take(cell, slot);
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Drops every key the predicate turns down
+    pub fn retain(&mut self, mut keep: impl FnMut(K) -> bool) {
+        self.entries.retain(|key, _| keep(*key));
+        self.cells.retain(|cell, entries| {
+            entries.retain(|(key, _)| keep(*key));
+            for (slot, (key, _)) in entries.iter().enumerate() {
+                self.entries.insert(*key, (*cell, slot));
+            }
+            !entries.is_empty()
+        });
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.cells.clear();
+    }
+
+    pub fn occupied_cells(&self) -> usize {
+        self.cells.len()
+    }
+
+    // Swap removal moves the cell's last entry into the gap, so its slot is fixed up
+    fn take(&mut self, cell: Cell, slot: usize) {
+        let entries = self.cells.get_mut(&cell).expect("filed cell exists");
+        entries.swap_remove(slot);
+
+        if entries.is_empty() {
+            self.cells.remove(&cell);
+        } else if let Some((moved, _)) = entries.get(slot) {
+            self.entries.insert(*moved, (cell, slot));
+        }
+    }
+
+    /*
+        Calls visit on every entry in the cells covering min to max.
+        A range spanning more cells than are occupied walks the
+        occupied ones instead, so huge or infinite ranges stay cheap.
+    */
+    fn visit_cells(&self, min: Vec2, max: Vec2, mut visit: impl FnMut(K, Vec2)) {
+        let (min_x, min_y) = self.cell_of(min);
+        let (max_x, max_y) = self.cell_of(max);
+
+        let span = |min: i32, max: i32| (max as i64 - min as i64 + 1).max(0) as u64;
+        if span(min_x, max_x).saturating_mul(span(min_y, max_y)) > self.cells.len() as u64 {
+            for (&(x, y), cell) in &self.cells {
+                if (min_x..=max_x).contains(&x) && (min_y..=max_y).contains(&y) {
+                    for (key, point) in cell {
+                        visit(*key, *point);
+                    }
+                }
+            }
+            return;
+        }
+
+        for x in min_x..=max_x {
+            for y in min_y..=max_y {
+                let Some(cell) = self.cells.get(&(x, y)) else {
+                    continue;
+                };
+
+                for (key, point) in cell {
+                    visit(*key, *point);
+                }
+            }
+        }
+    }
+
+    // Keys of every point within radius of centre, in no set order
+    pub fn query_radius(&self, centre: Vec2, radius: f32, found: &mut Vec<K>) {
+        found.clear();
+
+        let reach = Vec2::new(radius, radius);
+        let radius_squared = radius * radius;
+
+        self.visit_cells(centre - reach, centre + reach, |key, point| {
+            if (point - centre).length_squared() <= radius_squared {
+                found.push(key);
+            }
+        });
+    }
+
+    // Keys of every point inside the box from min to max, edges included
+    pub fn query_rect(&self, min: Vec2, max: Vec2, found: &mut Vec<K>) {
+        found.clear();
+
+        self.visit_cells(min, max, |key, point| {
+            if point.x >= min.x && point.x <= max.x && point.y >= min.y && point.y <= max.y {
+                found.push(key);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Spread of points that lands on cell edges and negative coordinates
+    fn scatter() -> Vec<Vec2> {
+        (0..400)
+            .map(|index| {
+                let t = index as f32;
+                Vec2::new((t * 37.0) % 230.0 - 60.0, (t * 53.0) % 190.0 - 40.0)
+            })
+            .collect()
+    }
+
+    fn brute_force(points: &[Vec2], keep: impl Fn(Vec2) -> bool) -> Vec<usize> {
+        points
+            .iter()
+            .enumerate()
+            .filter(|(_, point)| keep(**point))
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    #[test]
+    fn radius_query_matches_brute_force() {
+        let points = scatter();
+        let grid = SpatialGrid::from_points(25.0, &points);
+        let mut found = vec![];
+
+        for (centre, radius) in [
+            (Vec2::new(0.0, 0.0), 30.0),
+            (Vec2::new(100.0, 50.0), 10.0),
+            (Vec2::new(-60.0, -40.0), 75.0),
+            (Vec2::new(500.0, 500.0), 20.0),
+        ] {
+            grid.query_radius(centre, radius, &mut found);
+            found.sort();
+            assert_eq!(
+                found,
+                brute_force(&points, |point| point.distance(centre) <= radius)
+            );
+        }
+    }
+
+    #[test]
+    fn rect_query_matches_brute_force() {
+        let points = scatter();
+        let grid = SpatialGrid::from_points(25.0, &points);
+        let mut found = vec![];
+
+        for (min, max) in [
+            (Vec2::new(-10.0, -10.0), Vec2::new(40.0, 20.0)),
+            (Vec2::new(-60.0, -40.0), Vec2::new(170.0, 150.0)),
+            (Vec2::new(10.0, 10.0), Vec2::new(10.5, 10.5)),
+        ] {
+            grid.query_rect(min, max, &mut found);
+            found.sort();
+            assert_eq!(
+                found,
+                brute_force(&points, |p| p.x >= min.x
+                    && p.x <= max.x
+                    && p.y >= min.y
+                    && p.y <= max.y)
+            );
+        }
+    }
+
+    #[test]
+    fn huge_ranges_walk_occupied_cells() {
+        let points = scatter();
+        let grid = SpatialGrid::from_points(1.0, &points);
+
+        let mut found = vec![];
+        grid.query_radius(Vec2::ZERO, 1e30, &mut found);
+        assert_eq!(found.len(), points.len());
+
+        grid.query_rect(
+            Vec2::new(f32::NEG_INFINITY, 0.0),
+            Vec2::new(f32::INFINITY, f32::INFINITY),
+            &mut found,
+        );
+        let expected = points.iter().filter(|p| p.y >= 0.0).count();
+        assert_eq!(found.len(), expected);
+    }
+
+    #[test]
+    fn moving_keys_updates_queries() {
+        let mut points = scatter();
+        let mut grid = SpatialGrid::from_points(25.0, &points);
+
+        // Some move a little within their cell, some a long way
+        for (index, point) in points.iter_mut().enumerate() {
+            *point += Vec2::new((index % 7) as f32 * 20.0 - 60.0, (index % 3) as f32);
+            grid.insert(index, *point);
+        }
+        assert!(grid.remove(3));
+        assert!(!grid.remove(3));
+        assert_eq!(grid.len(), points.len() - 1);
+
+        let mut found = vec![];
+        let centre = Vec2::new(20.0, 30.0);
+        grid.query_radius(centre, 50.0, &mut found);
+        found.sort();
+
+        let mut expected = brute_force(&points, |point| point.distance(centre) <= 50.0);
+        expected.retain(|index| *index != 3);
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn emptied_cells_are_dropped() {
+        // One point swimming across a row of cells only ever fills one
+        let mut grid = SpatialGrid::new(10.0);
+        for step in 0..100 {
+            grid.insert(0, Vec2::new(step as f32 * 5.0, 0.0));
+            assert_eq!(grid.occupied_cells(), 1);
+        }
+
+        grid.insert(1, Vec2::new(-25.0, 0.0));
+        grid.insert(2, Vec2::new(-25.0, 3.0));
+        assert_eq!(grid.occupied_cells(), 2);
+        grid.retain(|key| key != 0);
+        assert_eq!(grid.occupied_cells(), 1);
+        assert!(grid.remove(1));
+        assert_eq!(grid.occupied_cells(), 1);
+        assert!(grid.remove(2));
+        assert_eq!(grid.occupied_cells(), 0);
+    }
+
+    #[test]
+    fn clear_empties_the_grid() {
+        let mut grid = SpatialGrid::from_points(10.0, &scatter());
+        grid.clear();
+        assert!(grid.is_empty());
+        assert_eq!(grid.occupied_cells(), 0);
+
+        let mut found = vec![1];
+        grid.query_radius(Vec2::ZERO, 1000.0, &mut found);
+        assert!(found.is_empty());
+    }
+}
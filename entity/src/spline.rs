@@ -0,0 +1,174 @@
+use crate::vector::Vec2;
+
+// Centripetal parameterisation, keeps the curve from looping or overshooting
+// where points bunch up like at the caps of an outline
+const ALPHA: f32 = 0.5;
+
+// Points closer than this are treated as the same point
+const MIN_GAP: f32 = 1e-3;
+
+/*
+    Closed Catmull-Rom spline through every point of a loop,
+    sampled samples_per_segment times between each pair of points.
+
+    The curve passes through each original point, which is always
+    the first sample of its segment, and the last segment joins
+    back to the first point.
+*/
+pub fn catmull_rom_closed(points: &[Vec2], samples_per_segment: usize) -> Vec<Vec2> {
+    let points = dedup_closed(points);
+    let count = points.len();
+
+    // Too few points to bend round, nothing to smooth
+    if count < 3 || samples_per_segment <= 1 {
+        return points;
+    }
+
+    let mut smoothed = Vec::with_capacity(count * samples_per_segment);
+
+    for index in 0..count {
+        let p0 = points[(index + count - 1) % count];
+        let p1 = points[index];
+        let p2 = points[(index + 1) % count];
+        let p3 = points[(index + 2) % count];
+
+        for step in 0..samples_per_segment {
+            let t = step as f32 / samples_per_segment as f32;
+            smoothed.push(segment_point(p0, p1, p2, p3, t));
+        }
+    }
+
+    smoothed
+}
+
+// Drops repeats, including the last point repeating the first
+fn dedup_closed(points: &[Vec2]) -> Vec<Vec2> {
+    let mut unique: Vec<Vec2> = Vec::with_capacity(points.len());
+
+    for point in points {
+        if unique
+            .last()
+            .is_none_or(|last| last.distance(*point) > MIN_GAP)
+        {
+            unique.push(*point);
+        }
+    }
+
+    while unique.len() > 1 && unique[0].distance(unique[unique.len() - 1]) <= MIN_GAP {
+        unique.pop();
+    }
+
+    unique
+}
+
+/*
+    Barry & Goldman's pyramid form of the curve between p1 and p2,
+    where u runs 0..1 across that segment.
+
+    Knots are spaced by distance ^ alpha
+        t0 = 0
+        t1 = t0 + |p1 - p0| ^ alpha
+        t2 = t1 + |p2 - p1| ^ alpha
+        t3 = t2 + |p3 - p2| ^ alpha
+
+    t = lerp( t1, t2, u )
+
+    a1 = lerp( p0, p1, (t - t0) / (t1 - t0) )
+    a2 = lerp( p1, p2, (t - t1) / (t2 - t1) )
+    a3 = lerp( p2, p3, (t - t2) / (t3 - t2) )
+
+    b1 = lerp( a1, a2, (t - t0) / (t2 - t0) )
+    b2 = lerp( a2, a3, (t - t1) / (t3 - t1) )
+
+    point = lerp( b1, b2, (t - t1) / (t2 - t1) )
+*/
+fn segment_point(p0: Vec2, p1: Vec2, p2: Vec2, p3: Vec2, u: f32) -> Vec2 {
+    let t0 = 0.0;
+    let t1 = t0 + knot_gap(p0, p1);
+    let t2 = t1 + knot_gap(p1, p2);
+    let t3 = t2 + knot_gap(p2, p3);
+
+    let t = t1 + (t2 - t1) * u;
+
+    let a1 = p0.lerp(p1, (t - t0) / (t1 - t0));
+    let a2 = p1.lerp(p2, (t - t1) / (t2 - t1));
+    let a3 = p2.lerp(p3, (t - t2) / (t3 - t2));
+
+    let b1 = a1.lerp(a2, (t - t0) / (t2 - t0));
+    let b2 = a2.lerp(a3, (t - t1) / (t3 - t1));
+
+    b1.lerp(b2, (t - t1) / (t2 - t1))
+}
+
+fn knot_gap(a: Vec2, b: Vec2) -> f32 {
+    a.distance(b).max(MIN_GAP).powf(ALPHA)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+
+    use super::*;
+
+    fn square() -> Vec<Vec2> {
+        vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(10.0, 0.0),
+            Vec2::new(10.0, 10.0),
+            Vec2::new(0.0, 10.0),
+        ]
+    }
+
+    #[test]
+    fn passes_through_points() {
+        let points = square();
+        let smoothed = catmull_rom_closed(&points, 6);
+
+        assert_eq!(smoothed.len(), points.len() * 6);
+        for (index, point) in points.iter().enumerate() {
+            assert!(smoothed[index * 6].distance(*point) < 1e-4);
+        }
+    }
+
+    #[test]
+    fn rounds_off_corners() {
+        let smoothed = catmull_rom_closed(&square(), 8);
+        let centre = Vec2::new(5.0, 5.0);
+
+        // Midway along each side bulges out past the straight edge
+        for index in 0..4 {
+            let mid = smoothed[index * 8 + 4];
+            assert!(mid.distance(centre) > 5.0);
+            assert!(mid.distance(centre) < 5.0 * 2f32.sqrt());
+        }
+    }
+
+    #[test]
+    fn circle_stays_round() {
+        let points: Vec<Vec2> = (0..12)
+            .map(|step| Vec2::from_angle(step as f32 * PI / 6.0) * 50.0)
+            .collect();
+
+        for point in catmull_rom_closed(&points, 10) {
+            assert!((point.length() - 50.0).abs() < 0.5, "{point}");
+        }
+    }
+
+    #[test]
+    fn duplicate_points_are_ignored() {
+        let mut points = square();
+        points.insert(1, Vec2::new(0.0, 0.0));
+        points.push(Vec2::new(0.0, 0.0));
+
+        let smoothed = catmull_rom_closed(&points, 4);
+        assert_eq!(smoothed.len(), 4 * 4);
+        assert!(smoothed.iter().all(|p| p.x.is_finite() && p.y.is_finite()));
+    }
+
+    #[test]
+    fn too_few_points_unchanged() {
+        let line = vec![Vec2::new(0.0, 0.0), Vec2::new(4.0, 0.0)];
+        assert_eq!(catmull_rom_closed(&line, 5), line);
+        assert_eq!(catmull_rom_closed(&square(), 1), square());
+    }
+}
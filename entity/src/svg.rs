@@ -0,0 +1,571 @@
+use std::{f32::consts::TAU, fmt::Write};
+
+use crate::{
+    chain::Chain,
+    creature::Creature,
+    render::{arc_points, draw_chain, draw_creature, draw_skeleton, draw_spine, Colour, Renderer},
+    vector::Vec2,
+    world::World,
+};
+
+// How an element is filled and outlined, None leaves that part out
+#[derive(Debug, Clone, PartialEq)]
+struct Paint {
+    fill: Option<Colour>,
+    stroke: Option<(Colour, f32)>,
+}
+
+// One element of a drawn pose
+#[derive(Debug, Clone, PartialEq)]
+enum Shape {
+    Path {
+        paint: Paint,
+        points: Vec<Vec2>,
+        closed: bool,
+    },
+    Circle {
+        paint: Paint,
+        centre: Vec2,
+        radius: f32,
+    },
+}
+
+/*
+    Renderer that keeps every shape it is asked to draw, in order, to be
+    written out as SVG elements. Colours become inline fill and stroke
+    attributes, strokes round off their ends and corners as the window's do.
+
+    A closed outline traced round the polygon or circle drawn just before
+    it is folded into that element rather than written again.
+*/
+#[derive(Debug, Clone, Default)]
+pub struct SvgRenderer {
+    shapes: Vec<Shape>,
+}
+
+impl SvgRenderer {
+    pub fn new() -> SvgRenderer {
+        SvgRenderer::default()
+    }
+
+    // Everything drawn so far as a standalone SVG document
+    pub fn to_svg(&self, width: f32, height: f32) -> String {
+        document(&[("body", &self.shapes)], width, height)
+    }
+}
+
+impl Renderer for SvgRenderer {
+    fn fill_polygon(&mut self, colour: Colour, points: &[Vec2]) {
+        self.shapes.push(Shape::Path {
+            paint: Paint {
+                fill: Some(colour),
+                stroke: None,
+            },
+            points: points.to_vec(),
+            closed: true,
+        });
+    }
+
+    fn stroke_path(&mut self, colour: Colour, width: f32, points: &[Vec2], closed: bool) {
+        if let (
+            true,
+            Some(Shape::Path {
+                paint,
+                points: filled,
+                closed: true,
+            }),
+        ) = (closed, self.shapes.last_mut())
+        {
+            if paint.stroke.is_none() && filled == points {
+                paint.stroke = Some((colour, width));
+                return;
+            }
+        }
+
+        self.shapes.push(Shape::Path {
+            paint: Paint {
+                fill: None,
+                stroke: Some((colour, width)),
+            },
+            points: points.to_vec(),
+            closed,
+        });
+    }
+
+    fn circle(&mut self, colour: Colour, centre: Vec2, radius: f32) {
+        self.shapes.push(Shape::Circle {
+            paint: Paint {
+                fill: Some(colour),
+                stroke: None,
+            },
+            centre,
+            radius,
+        });
+    }
+
+    // A full turn is a circle outline, anything less is broken into straight pieces
+    fn arc(&mut self, colour: Colour, width: f32, centre: Vec2, radius: f32, start: f32, end: f32) {
+        let sweep = end - start;
+
+        if sweep.abs() >= TAU {
+            if let Some(Shape::Circle {
+                paint,
+                centre: filled,
+                radius: filled_radius,
+            }) = self.shapes.last_mut()
+            {
+                if paint.stroke.is_none() && *filled == centre && *filled_radius == radius {
+                    paint.stroke = Some((colour, width));
+                    return;
+                }
+            }
+
+            self.shapes.push(Shape::Circle {
+                paint: Paint {
+                    fill: None,
+                    stroke: Some((colour, width)),
+                },
+                centre,
+                radius,
+            });
+            return;
+        }
+
+        let points = arc_points(centre, radius, start, end);
+        self.stroke_path(colour, width, &points, false);
+    }
+
+    // Left out so every frame of an animation holds the same shapes
+    fn text(&mut self, _colour: Colour, _size: u32, _position: Vec2, _text: &str) {}
+}
+
+// #rrggbb, alpha is written separately as an opacity
+fn hex(colour: Colour) -> String {
+    let [r, g, b] = [colour[0], colour[1], colour[2]]
+        .map(|channel| (channel.clamp(0.0, 1.0) * 255.0).round() as u8);
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+fn paint_attributes(paint: &Paint) -> String {
+    let mut attributes = String::new();
+
+    match paint.fill {
+        Some(colour) => {
+            write!(attributes, r#" fill="{}""#, hex(colour)).unwrap();
+            if colour[3] < 1.0 {
+                write!(attributes, r#" fill-opacity="{:.3}""#, colour[3].max(0.0)).unwrap();
+            }
+        }
+        None => attributes.push_str(r#" fill="none""#),
+    }
+
+    if let Some((colour, width)) = paint.stroke {
+        write!(
+            attributes,
+            r#" stroke="{}" stroke-width="{width}" stroke-linecap="round" stroke-linejoin="round""#,
+            hex(colour)
+        )
+        .unwrap();
+        if colour[3] < 1.0 {
+            write!(attributes, r#" stroke-opacity="{:.3}""#, colour[3].max(0.0)).unwrap();
+        }
+    }
+
+    attributes
+}
+
+// Path data like M 1.00 2.00 L 3.00 4.00 Z
+fn path_data(points: &[Vec2], closed: bool) -> String {
+    let mut d = String::new();
+    for (index, point) in points.iter().enumerate() {
+        let command = if index == 0 { 'M' } else { 'L' };
+        write!(d, "{command} {:.2} {:.2} ", point.x, point.y).unwrap();
+    }
+    if closed && !points.is_empty() {
+        d.push('Z');
+    }
+    d.trim_end().to_string()
+}
+
+fn circle_values(centre: Vec2, radius: f32) -> [String; 3] {
+    [centre.x, centre.y, radius].map(|value| format!("{value:.2}"))
+}
+
+fn header(svg: &mut String, width: f32, height: f32) {
+    writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#
+    )
+    .unwrap();
+}
+
+/*
+    The skeleton sits in its own group after the body, so a page
+    embedding the file can hide it with .skeleton { display: none }.
+*/
+fn document(groups: &[(&str, &[Shape])], width: f32, height: f32) -> String {
+    let mut svg = String::new();
+    header(&mut svg, width, height);
+
+    for (group, shapes) in groups {
+        writeln!(svg, r#"<g class="{group}">"#).unwrap();
+        for shape in *shapes {
+            match shape {
+                Shape::Path {
+                    paint,
+                    points,
+                    closed,
+                } => writeln!(
+                    svg,
+                    r#"<path d="{}"{}/>"#,
+                    path_data(points, *closed),
+                    paint_attributes(paint)
+                ),
+                Shape::Circle {
+                    paint,
+                    centre,
+                    radius,
+                } => {
+                    let [cx, cy, r] = circle_values(*centre, *radius);
+                    writeln!(
+                        svg,
+                        r#"<circle cx="{cx}" cy="{cy}" r="{r}"{}/>"#,
+                        paint_attributes(paint)
+                    )
+                }
+            }
+            .unwrap();
+        }
+        svg.push_str("</g>\n");
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+// One drawn frame, body and skeleton recorded apart
+#[derive(Debug, Default)]
+struct Pose {
+    body: SvgRenderer,
+    skeleton: SvgRenderer,
+}
+
+impl Pose {
+    fn creature(creature: &Creature) -> Pose {
+        let mut pose = Pose::default();
+        pose.draw(creature);
+        pose
+    }
+
+    fn draw(&mut self, creature: &Creature) {
+        draw_creature(&mut self.body, creature);
+        draw_skeleton(&mut self.skeleton, creature);
+    }
+
+    fn groups(&self) -> [(&'static str, &[Shape]); 2] {
+        [
+            ("body", &self.body.shapes),
+            ("skeleton", &self.skeleton.shapes),
+        ]
+    }
+
+    fn to_svg(&self, width: f32, height: f32) -> String {
+        document(&self.groups(), width, height)
+    }
+}
+
+// Current pose as a standalone SVG document
+pub fn creature_svg(creature: &Creature, width: f32, height: f32) -> String {
+    Pose::creature(creature).to_svg(width, height)
+}
+
+// Every creature in the world as it stands, in the order the window draws them
+pub fn world_svg(world: &World, width: f32, height: f32) -> String {
+    let mut pose = Pose::default();
+    for (_, creature) in world.iter() {
+        pose.draw(creature);
+    }
+    pose.to_svg(width, height)
+}
+
+impl Chain {
+    // Current pose, sized to the chain's arena
+    pub fn to_svg(&self) -> String {
+        let mut pose = Pose::default();
+        draw_chain(&mut pose.body, self);
+        draw_spine(&mut pose.skeleton, self);
+        pose.to_svg(self.arena().width(), self.arena().height())
+    }
+}
+
+/*
+    One SMIL animate per attribute, stepping through a value for each
+    frame and looping.
+
+    dur = frames * dt
+
+    calcMode discrete holds each frame for dt rather than tweening, so
+    outlines whose point count changes between frames still play back.
+*/
+fn animate(svg: &mut String, attribute: &str, values: &[String], dur: f32) {
+    if values.windows(2).all(|pair| pair[0] == pair[1]) {
+        return;
+    }
+
+    writeln!(
+        svg,
+        r#"  <animate attributeName="{attribute}" dur="{dur:.3}s" repeatCount="indefinite" calcMode="discrete" values="{}"/>"#,
+        values.join(";")
+    )
+    .unwrap();
+}
+
+/*
+    Shapes line up across frames by the order they were drawn in, styled
+    as in the first frame. A frame with no shape at an index, or one of
+    another kind, holds the last value seen so the element never jumps
+    to nothing.
+*/
+fn animate_poses(poses: &[Pose], dt: f32, width: f32, height: f32) -> String {
+    let Some(first) = poses.first() else {
+        return Pose::default().to_svg(width, height);
+    };
+    let dur = dt * poses.len() as f32;
+
+    let mut svg = String::new();
+    header(&mut svg, width, height);
+
+    for (group, (name, shapes)) in first.groups().into_iter().enumerate() {
+        writeln!(svg, r#"<g class="{name}">"#).unwrap();
+
+        for (index, shape) in shapes.iter().enumerate() {
+            let frames = poses.iter().map(|pose| pose.groups()[group].1.get(index));
+
+            match shape {
+                Shape::Path {
+                    paint,
+                    points,
+                    closed,
+                } => {
+                    let mut held = path_data(points, *closed);
+                    let values: Vec<String> = frames
+                        .map(|frame| {
+                            if let Some(Shape::Path { points, closed, .. }) = frame {
+                                held = path_data(points, *closed);
+                            }
+                            held.clone()
+                        })
+                        .collect();
+
+                    writeln!(
+                        svg,
+                        r#"<path d="{}"{}>"#,
+                        values[0],
+                        paint_attributes(paint)
+                    )
+                    .unwrap();
+                    animate(&mut svg, "d", &values, dur);
+                    svg.push_str("</path>\n");
+                }
+                Shape::Circle {
+                    paint,
+                    centre,
+                    radius,
+                } => {
+                    let mut held = circle_values(*centre, *radius);
+                    let mut values = [vec![], vec![], vec![]];
+                    for frame in frames {
+                        if let Some(Shape::Circle { centre, radius, .. }) = frame {
+                            held = circle_values(*centre, *radius);
+                        }
+                        for (values, value) in values.iter_mut().zip(&held) {
+                            values.push(value.clone());
+                        }
+                    }
+
+                    writeln!(
+                        svg,
+                        r#"<circle cx="{}" cy="{}" r="{}"{}>"#,
+                        values[0][0],
+                        values[1][0],
+                        values[2][0],
+                        paint_attributes(paint)
+                    )
+                    .unwrap();
+                    for (attribute, values) in ["cx", "cy", "r"].iter().zip(&values) {
+                        animate(&mut svg, attribute, values, dur);
+                    }
+                    svg.push_str("</circle>\n");
+                }
+            }
+        }
+
+        svg.push_str("</g>\n");
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/*
+    Every frame of one creature as a looping animated SVG, dt seconds
+    apart. Frames are expected to be the same creature as it moves,
+    extra shapes in later frames are left out.
+*/
+pub fn animated_svg(frames: &[Creature], dt: f32, width: f32, height: f32) -> String {
+    let poses: Vec<Pose> = frames.iter().map(Pose::creature).collect();
+    animate_poses(&poses, dt, width, height)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        fish::Fish,
+        lizard::Lizard,
+        render::{BODY, EYE, FIN, NODE, OUTLINE},
+    };
+
+    fn count(svg: &str, needle: &str) -> usize {
+        svg.matches(needle).count()
+    }
+
+    // Entries of the nth animate's values list
+    fn animated_values(svg: &str, nth: usize) -> Vec<String> {
+        let values = svg.split(r#"values=""#).nth(nth + 1).unwrap();
+        let values = &values[..values.find('"').unwrap()];
+        values.split(';').map(String::from).collect()
+    }
+
+    #[test]
+    fn chain_pose_has_outline_eyes_and_skeleton() {
+        let chain = Chain::builder()
+            .position(100.0, 100.0)
+            .radials([10.0, 8.0, 6.0])
+            .build()
+            .unwrap();
+        let svg = chain.to_svg();
+
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.ends_with("</svg>\n"));
+        assert!(svg.contains(r#"viewBox="0 0 1000 1000""#));
+
+        // The outline is filled and stroked as one element
+        let body = format!(r#"fill="{}" stroke="{}""#, hex(BODY), hex(OUTLINE));
+        assert_eq!(count(&svg, &body), 1);
+        assert_eq!(count(&svg, &format!(r#"fill="{}""#, hex(EYE))), 2);
+
+        let skeleton = &svg[svg.find(r#"<g class="skeleton">"#).unwrap()..];
+        assert_eq!(count(skeleton, "<path"), 1);
+        assert_eq!(count(skeleton, &format!(r#"fill="{}""#, hex(NODE))), 4);
+    }
+
+    #[test]
+    fn fish_pose_has_every_fin() {
+        let fish = Fish::preset(300.0, 300.0, 800.0, 600.0).unwrap();
+        let fins = fish.fin_polygons().len() + 1;
+        let svg = creature_svg(&fish.into(), 800.0, 600.0);
+
+        assert_eq!(count(&svg, &format!(r#"fill="{}""#, hex(FIN))), fins);
+    }
+
+    #[test]
+    fn world_pose_holds_every_creature() {
+        let fish = Fish::preset(300.0, 300.0, 800.0, 600.0).unwrap();
+        let lizard = Lizard::preset(200.0, 400.0, 800.0, 600.0).unwrap();
+        let mut world = World::new();
+        world.add(fish.clone());
+        world.add(lizard.clone());
+
+        let svg = world_svg(&world, 800.0, 600.0);
+        let paths = count(&creature_svg(&fish.into(), 800.0, 600.0), "<path")
+            + count(&creature_svg(&lizard.into(), 800.0, 600.0), "<path");
+        assert_eq!(count(&svg, "<path"), paths);
+        assert_eq!(count(&svg, "<svg"), 1);
+    }
+
+    #[test]
+    fn full_arc_folds_into_the_circle_under_it() {
+        let mut svg = SvgRenderer::new();
+        svg.circle(BODY, Vec2::new(10.0, 10.0), 5.0);
+        svg.arc(OUTLINE, 2.0, Vec2::new(10.0, 10.0), 5.0, 0.0, TAU);
+        assert_eq!(svg.shapes.len(), 1);
+
+        // Part of a turn is an open path
+        svg.arc(OUTLINE, 2.0, Vec2::new(10.0, 10.0), 5.0, 0.0, TAU / 4.0);
+        assert!(matches!(&svg.shapes[1], Shape::Path { closed: false, .. }));
+    }
+
+    #[test]
+    fn colours_become_attributes() {
+        assert_eq!(hex([1.0, 0.5, 0.0, 1.0]), "#ff8000");
+
+        let paint = Paint {
+            fill: Some([0.0, 0.0, 0.0, 0.5]),
+            stroke: None,
+        };
+        assert_eq!(
+            paint_attributes(&paint),
+            r##" fill="#000000" fill-opacity="0.500""##
+        );
+    }
+
+    #[test]
+    fn path_data_closes_when_asked() {
+        let points = [Vec2::new(0.0, 0.0), Vec2::new(1.5, 2.0)];
+        assert_eq!(path_data(&points, false), "M 0.00 0.00 L 1.50 2.00");
+        assert_eq!(path_data(&points, true), "M 0.00 0.00 L 1.50 2.00 Z");
+    }
+
+    #[test]
+    fn animation_steps_through_frames() {
+        let mut lizard = Lizard::preset(200.0, 300.0, 800.0, 600.0).unwrap();
+        lizard.chain.seek(Vec2::new(600.0, 300.0));
+
+        let mut frames = vec![];
+        for _ in 0..12 {
+            lizard.travel(1.0 / 30.0);
+            frames.push(Creature::from(lizard.clone()));
+        }
+
+        let svg = animated_svg(&frames, 1.0 / 30.0, 800.0, 600.0);
+        assert!(svg.contains(r#"dur="0.400s""#));
+        assert!(svg.contains(r#"<animate attributeName="d""#));
+        assert!(svg.contains(r#"<animate attributeName="cx""#));
+
+        // Each animated value lists one entry per frame
+        assert_eq!(animated_values(&svg, 0).len(), 12);
+
+        // Nothing moves with a single frame, so nothing is animated
+        let still = animated_svg(&frames[..1], 1.0 / 30.0, 800.0, 600.0);
+        assert!(!still.contains("<animate"));
+    }
+
+    #[test]
+    fn mismatched_shapes_hold_their_last_value() {
+        let triangle = [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(4.0, 0.0),
+            Vec2::new(4.0, 4.0),
+        ];
+        let moved = triangle.map(|point| point + Vec2::new(1.0, 0.0));
+
+        // The middle frame draws a circle where the others draw a path, and the other way round
+        let mut poses = vec![Pose::default(), Pose::default(), Pose::default()];
+        poses[0].body.fill_polygon(BODY, &triangle);
+        poses[0].body.circle(EYE, Vec2::new(2.0, 2.0), 1.0);
+        poses[1].body.circle(EYE, Vec2::new(9.0, 9.0), 1.0);
+        poses[1].body.fill_polygon(BODY, &triangle);
+        poses[2].body.fill_polygon(BODY, &moved);
+        poses[2].body.circle(EYE, Vec2::new(4.0, 2.0), 1.0);
+
+        let svg = animate_poses(&poses, 0.1, 20.0, 20.0);
+        assert_eq!(
+            animated_values(&svg, 0),
+            [&triangle, &triangle, &moved].map(|points| path_data(points, true))
+        );
+        assert_eq!(animated_values(&svg, 1), ["2.00", "2.00", "4.00"]);
+        assert!(!svg.contains(";;"));
+        assert!(!svg.contains(r#"r="0.00""#));
+    }
+}
@@ -0,0 +1,241 @@
+use std::{
+    fmt::Display,
+    ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub, SubAssign},
+};
+
+/*
+    2D vector used for both positions and directions.
+
+    Angles follow the screen convention of the rest of the crate,
+    y points down so a positive rotation turns clockwise on screen.
+        Right = 0
+        down = π / 2
+        left = π
+        up = - π / 2
+*/
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+pub struct Vec2 {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Vec2 {
+    pub const ZERO: Vec2 = Vec2 { x: 0.0, y: 0.0 };
+
+    pub const fn new(x: f32, y: f32) -> Vec2 {
+        Vec2 { x, y }
+    }
+
+    // Unit vector pointing along theta
+    pub fn from_angle(theta: f32) -> Vec2 {
+        Vec2 {
+            x: theta.cos(),
+            y: theta.sin(),
+        }
+    }
+
+    pub fn dot(self, other: Vec2) -> f32 {
+        self.x * other.x + self.y * other.y
+    }
+
+    /*
+        z component of the 3D cross product.
+        With y pointing down this is positive when other is clockwise of self.
+    */
+    pub fn cross(self, other: Vec2) -> f32 {
+        self.x * other.y - self.y * other.x
+    }
+
+    pub fn length_squared(self) -> f32 {
+        self.dot(self)
+    }
+
+    pub fn length(self) -> f32 {
+        self.length_squared().sqrt()
+    }
+
+    pub fn distance(self, other: Vec2) -> f32 {
+        (other - self).length()
+    }
+
+    // Zero length vectors have no direction so stay at zero
+    pub fn normalize(self) -> Vec2 {
+        let length = self.length();
+
+        if length == 0.0 {
+            return Vec2::ZERO;
+        }
+
+        self / length
+    }
+
+    /*
+        x' = x * cos(theta) - y * sin(theta)
+        y' = x * sin(theta) + y * cos(theta)
+    */
+    pub fn rotate(self, theta: f32) -> Vec2 {
+        let (sin, cos) = theta.sin_cos();
+
+        Vec2 {
+            x: self.x * cos - self.y * sin,
+            y: self.x * sin + self.y * cos,
+        }
+    }
+
+    // t = 0 gives self, t = 1 gives other
+    pub fn lerp(self, other: Vec2, t: f32) -> Vec2 {
+        self + (other - self) * t
+    }
+
+    // Heading of the vector in -π..=π
+    pub fn angle(self) -> f32 {
+        self.y.atan2(self.x)
+    }
+
+    // Signed turn from self to other in -π..=π
+    pub fn angle_to(self, other: Vec2) -> f32 {
+        self.cross(other).atan2(self.dot(other))
+    }
+}
+
+impl Display for Vec2 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+impl Add for Vec2 {
+    type Output = Vec2;
+
+    fn add(self, rhs: Vec2) -> Vec2 {
+        Vec2::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl AddAssign for Vec2 {
+    fn add_assign(&mut self, rhs: Vec2) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for Vec2 {
+    type Output = Vec2;
+
+    fn sub(self, rhs: Vec2) -> Vec2 {
+        Vec2::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl SubAssign for Vec2 {
+    fn sub_assign(&mut self, rhs: Vec2) {
+        *self = *self - rhs;
+    }
+}
+
+impl Mul<f32> for Vec2 {
+    type Output = Vec2;
+
+    fn mul(self, rhs: f32) -> Vec2 {
+        Vec2::new(self.x * rhs, self.y * rhs)
+    }
+}
+
+impl MulAssign<f32> for Vec2 {
+    fn mul_assign(&mut self, rhs: f32) {
+        *self = *self * rhs;
+    }
+}
+
+impl Div<f32> for Vec2 {
+    type Output = Vec2;
+
+    fn div(self, rhs: f32) -> Vec2 {
+        Vec2::new(self.x / rhs, self.y / rhs)
+    }
+}
+
+impl Neg for Vec2 {
+    type Output = Vec2;
+
+    fn neg(self) -> Vec2 {
+        Vec2::new(-self.x, -self.y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+
+    use super::*;
+
+    fn assert_close(a: Vec2, b: Vec2) {
+        assert!(a.distance(b) < 1e-5, "{a} is not {b}");
+    }
+
+    #[test]
+    fn arithmetic() {
+        let a = Vec2::new(3.0, -2.0);
+        let b = Vec2::new(1.0, 4.0);
+
+        assert_eq!(a + b, Vec2::new(4.0, 2.0));
+        assert_eq!(a - b, Vec2::new(2.0, -6.0));
+        assert_eq!(a * 2.0, Vec2::new(6.0, -4.0));
+        assert_eq!(a / 2.0, Vec2::new(1.5, -1.0));
+        assert_eq!(-a, Vec2::new(-3.0, 2.0));
+
+        let mut c = a;
+        c += b;
+        c -= Vec2::new(1.0, 1.0);
+        c *= 0.5;
+        assert_eq!(c, Vec2::new(1.5, 0.5));
+    }
+
+    #[test]
+    fn products() {
+        let right = Vec2::new(1.0, 0.0);
+        let down = Vec2::new(0.0, 1.0);
+
+        assert_eq!(right.dot(down), 0.0);
+        assert_eq!(right.dot(right * 3.0), 3.0);
+
+        // Down is clockwise of right on screen
+        assert_eq!(right.cross(down), 1.0);
+        assert_eq!(down.cross(right), -1.0);
+    }
+
+    #[test]
+    fn length_and_normalize() {
+        let v = Vec2::new(3.0, 4.0);
+
+        assert_eq!(v.length(), 5.0);
+        assert_eq!(v.length_squared(), 25.0);
+        assert_eq!(v.distance(Vec2::ZERO), 5.0);
+        assert_eq!(v.normalize(), Vec2::new(0.6, 0.8));
+        assert_eq!(Vec2::ZERO.normalize(), Vec2::ZERO);
+    }
+
+    #[test]
+    fn rotate_and_angles() {
+        let right = Vec2::new(2.0, 0.0);
+
+        assert_close(right.rotate(PI / 2.0), Vec2::new(0.0, 2.0));
+        assert_close(right.rotate(-PI), Vec2::new(-2.0, 0.0));
+        assert_close(Vec2::from_angle(-PI / 2.0), Vec2::new(0.0, -1.0));
+
+        assert_eq!(Vec2::new(-1.0, 0.0).angle(), PI);
+        assert_eq!(Vec2::new(0.0, 1.0).angle(), PI / 2.0);
+
+        assert_eq!(right.angle_to(Vec2::new(0.0, 5.0)), PI / 2.0);
+        assert_eq!(right.angle_to(Vec2::new(0.0, -5.0)), -PI / 2.0);
+    }
+
+    #[test]
+    fn lerp_between() {
+        let a = Vec2::new(0.0, 10.0);
+        let b = Vec2::new(10.0, 0.0);
+
+        assert_eq!(a.lerp(b, 0.0), a);
+        assert_eq!(a.lerp(b, 1.0), b);
+        assert_eq!(a.lerp(b, 0.25), Vec2::new(2.5, 7.5));
+    }
+}
@@ -0,0 +1,307 @@
+use std::{collections::HashSet, fmt::Display};
+
+use crate::{
+    creature::Creature,
+    spatial::{NodeKey, SpatialGrid},
+};
+
+/*
+    Handed out by a World when a creature is added and never reused,
+    so it keeps pointing at the same creature however many others
+    come and go.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CreatureId(u64);
+
+impl Display for CreatureId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+// Creatures are kept in the order they were added, which is also id order
+#[derive(Debug, Clone, Default)]
+pub struct World {
+    creatures: Vec<(CreatureId, Creature)>,
+    next_id: u64,
+}
+
+impl World {
+    pub fn new() -> World {
+        World::default()
+    }
+
+    pub fn add(&mut self, creature: impl Into<Creature>) -> CreatureId {
+        let id = CreatureId(self.next_id);
+        self.next_id += 1;
+        self.creatures.push((id, creature.into()));
+        id
+    }
+
+    pub fn remove(&mut self, id: CreatureId) -> Option<Creature> {
+        let index = self.index_of(id)?;
+        Some(self.creatures.remove(index).1)
+    }
+
+    pub fn get(&self, id: CreatureId) -> Option<&Creature> {
+        let index = self.index_of(id)?;
+        Some(&self.creatures[index].1)
+    }
+
+    pub fn get_mut(&mut self, id: CreatureId) -> Option<&mut Creature> {
+        let index = self.index_of(id)?;
+        Some(&mut self.creatures[index].1)
+    }
+
+    pub fn contains(&self, id: CreatureId) -> bool {
+        self.index_of(id).is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.creatures.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.creatures.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (CreatureId, &Creature)> {
+        self.creatures.iter().map(|(id, creature)| (*id, creature))
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (CreatureId, &mut Creature)> {
+        self.creatures
+            .iter_mut()
+            .map(|(id, creature)| (*id, creature))
+    }
+
+    // Advances every creature by the same dt
+    pub fn step(&mut self, dt: f32) {
+        for (_, creature) in &mut self.creatures {
+            creature.travel(dt);
+        }
+    }
+
+    /*
+        Blends each creature with the one holding the same id in next.
+        Creatures only in next appear as they are, ones removed
+        since self was taken are dropped.
+    */
+    pub fn interpolate(&self, next: &World, t: f32) -> World {
+        World {
+            creatures: next
+                .creatures
+                .iter()
+                .map(|(id, creature)| {
+                    let blended = match self.get(*id) {
+                        Some(prev) => prev.interpolate(creature, t),
+                        None => creature.clone(),
+                    };
+                    (*id, blended)
+                })
+                .collect(),
+            next_id: next.next_id,
+        }
+    }
+
+    /*
+        Brings the grid up to date with every head and body node,
+        meant to be called once a tick after step. Nodes that stayed
+        in their cell are only rewritten in place, ones belonging to
+        creatures no longer in the world are dropped.
+
+        An octopus has no chain so only its centre is indexed, under the head.
+    */
+    pub fn index_nodes(&self, grid: &mut SpatialGrid<NodeKey>) {
+        let mut seen = HashSet::new();
+        let mut count = 0;
+
+        for (id, creature) in self.iter() {
+            seen.insert(id);
+            count += 1;
+
+            let head = NodeKey {
+                creature: id,
+                node: None,
+            };
+            grid.insert(head, creature.position());
+
+            let Some(chain) = creature.chain() else {
+                continue;
+            };
+
+            for (index, node) in chain.head.children.iter().enumerate() {
+                let key = NodeKey {
+                    creature: id,
+                    node: Some(index),
+                };
+                grid.insert(key, node.point);
+                count += 1;
+            }
+        }
+
+        // Anything left over belongs to a creature that has gone
+        if grid.len() > count {
+            grid.retain(|key| seen.contains(&key.creature));
+        }
+    }
+
+    fn index_of(&self, id: CreatureId) -> Option<usize> {
+        self.creatures.binary_search_by_key(&id, |(id, _)| *id).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{chain::Chain, fish::Fish, octopus::Octopus, vector::Vec2};
+
+    fn chain_at(x: f32) -> Chain {
+        Chain::builder()
+            .position(x, 500.0)
+            .radials([8.0; 4])
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn ids_stay_stable_through_removal() {
+        let mut world = World::new();
+        let first = world.add(chain_at(100.0));
+        let second = world.add(chain_at(200.0));
+        let third = world.add(chain_at(300.0));
+
+        assert_eq!(
+            world.remove(second).map(|c| c.position()),
+            Some(Vec2::new(200.0, 500.0))
+        );
+        assert_eq!(world.len(), 2);
+        assert!(!world.contains(second));
+        assert!(world.remove(second).is_none());
+
+        // Ids are never handed out twice
+        let fourth = world.add(chain_at(400.0));
+        assert_ne!(fourth, second);
+
+        assert_eq!(
+            world.get(first).unwrap().position(),
+            Vec2::new(100.0, 500.0)
+        );
+        assert_eq!(
+            world.get(third).unwrap().position(),
+            Vec2::new(300.0, 500.0)
+        );
+        assert_eq!(
+            world.get(fourth).unwrap().position(),
+            Vec2::new(400.0, 500.0)
+        );
+
+        let ids: Vec<CreatureId> = world.iter().map(|(id, _)| id).collect();
+        assert_eq!(ids, vec![first, third, fourth]);
+    }
+
+    #[test]
+    fn step_moves_every_creature() {
+        let mut world = World::new();
+        world.add(chain_at(100.0));
+        world.add(Fish::preset(500.0, 500.0, 1000.0, 1000.0).unwrap());
+        world.add(Octopus::preset(800.0, 500.0, 1000.0, 1000.0).unwrap());
+
+        for (_, creature) in world.iter_mut() {
+            creature.seek(Vec2::new(500.0, 900.0));
+        }
+
+        let before = world.clone();
+        for _ in 0..10 {
+            world.step(1.0 / 30.0);
+        }
+
+        for ((_, moved), (_, start)) in world.iter().zip(before.iter()) {
+            match (moved, start) {
+                (Creature::Octopus(moved), Creature::Octopus(start)) => {
+                    let tip = |octopus: &Octopus| {
+                        octopus.tentacles[0]
+                            .chain
+                            .head
+                            .children
+                            .last()
+                            .unwrap()
+                            .point
+                    };
+                    assert_ne!(tip(moved), tip(start));
+                }
+                _ => assert_ne!(moved.position(), start.position()),
+            }
+        }
+    }
+
+    #[test]
+    fn interpolate_matches_by_id() {
+        let mut world = World::new();
+        let kept = world.add(chain_at(100.0));
+        let removed = world.add(chain_at(200.0));
+        world.get_mut(kept).unwrap().seek(Vec2::new(100.0, 900.0));
+
+        let prev = world.clone();
+        world.remove(removed);
+        let added = world.add(chain_at(600.0));
+        world.step(1.0 / 30.0);
+
+        let blended = prev.interpolate(&world, 0.5);
+        assert_eq!(blended.len(), 2);
+        assert!(!blended.contains(removed));
+        assert_eq!(
+            blended.get(added).unwrap().position(),
+            world.get(added).unwrap().position()
+        );
+
+        let halfway = prev
+            .get(kept)
+            .unwrap()
+            .position()
+            .lerp(world.get(kept).unwrap().position(), 0.5);
+        assert_eq!(blended.get(kept).unwrap().position(), halfway);
+    }
+
+    #[test]
+    fn world_index_follows_creatures() {
+        let mut world = World::new();
+        let chain = world.add(
+            Chain::builder()
+                .position(300.0, 300.0)
+                .radials([8.0; 5])
+                .build()
+                .unwrap(),
+        );
+        let octopus = world.add(Octopus::preset(700.0, 500.0, 1000.0, 1000.0).unwrap());
+
+        let mut grid = SpatialGrid::new(50.0);
+        world.index_nodes(&mut grid);
+        assert_eq!(grid.len(), 7);
+
+        world.get_mut(chain).unwrap().seek(Vec2::new(300.0, 900.0));
+        for _ in 0..20 {
+            world.step(1.0 / 30.0);
+            world.index_nodes(&mut grid);
+        }
+
+        // Every node turns up where the chain left it
+        let head = world.get(chain).unwrap().chain().unwrap().head.clone();
+        let mut found = vec![];
+        for (index, node) in head.children.iter().enumerate() {
+            grid.query_radius(node.point, 0.01, &mut found);
+            assert!(found.contains(&NodeKey {
+                creature: chain,
+                node: Some(index)
+            }));
+        }
+
+        world.remove(octopus);
+        world.index_nodes(&mut grid);
+        assert_eq!(grid.len(), 6);
+        assert!(!grid.contains(NodeKey {
+            creature: octopus,
+            node: None
+        }));
+    }
+}